@@ -1,8 +1,9 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 static DEFAULT_CONFIG_PATH: &str = "config.yaml";
+static DEFAULT_SOCKET_NAME: &str = "boom_you_up_r.sock";
 
 #[derive(Debug, Parser)]
 #[command(version)]
@@ -19,6 +20,32 @@ pub struct Args {
 pub enum Actions {
     /// 开始运行
     Run {
+        /// 指定自定义配置文件，可以多次指定以合并多个配置文件
+        #[arg(short, long, value_name = "PATH", default_value = DEFAULT_CONFIG_PATH)]
+        config: Vec<PathBuf>,
+
+        /// 指定自定义状态文件，默认为第一个配置文件所在目录下的同名 .state.yaml 文件
+        #[arg(short, long, value_name = "PATH")]
+        state: Option<PathBuf>,
+
+        /// 任务错过执行时间（如系统休眠或启动较晚）时的默认处理方式，覆盖配置文件中的设置，默认为 skip
+        #[arg(long, value_enum)]
+        misfire: Option<MisfirePolicy>,
+
+        /// 处理方式为 grace 时，允许补执行的最长延迟（秒），覆盖配置文件中的设置，默认为 300
+        #[arg(long, value_name = "SECONDS")]
+        grace: Option<u64>,
+
+        /// 以守护进程模式运行，监听控制套接字以接受 status、pause 等命令
+        #[arg(short, long)]
+        daemon: bool,
+
+        #[command(flatten)]
+        connection: Connection,
+    },
+
+    /// 格式化配置文件，保留其中的注释
+    Format {
         /// 指定自定义配置文件
         #[arg(short, long, value_name = "PATH", default_value = DEFAULT_CONFIG_PATH)]
         config: PathBuf,
@@ -31,6 +58,110 @@ pub enum Actions {
         config: PathBuf,
     },
 
+    /// 添加一个只执行一次的任务，执行后自动失效
+    AddOnce {
+        /// 指定自定义配置文件，任务将写入其所在目录下的同名 .once.yaml 文件
+        #[arg(short, long, value_name = "PATH", default_value = DEFAULT_CONFIG_PATH)]
+        config: PathBuf,
+
+        /// 执行时间，格式为 "2026-10-20 14:30[:00]"、"14:30[:00]"（今天，若已过则为明天）或 "+90s"、"+15m"、"+2h"、"+1d"（从现在起）
+        at: String,
+
+        /// 要执行的命令或文件
+        command: String,
+
+        /// 要传递给命令的参数
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+
+        /// 使用指定的 Shell（如 sh、bash、cmd、powershell 或其路径）将命令作为脚本执行
+        #[arg(short, long, value_name = "SHELL")]
+        shell: Option<String>,
+
+        /// 使用内置播放器播放音频文件
+        #[arg(short, long)]
+        audio: bool,
+
+        /// 提前指定秒数发送系统通知
+        #[arg(short, long, value_name = "SECONDS")]
+        notify: Option<u32>,
+    },
+
+    /// 倒计时，到时后可以发送系统通知或播放音频
+    Timer {
+        /// 时长，如 "25m"、"1h30m"、"90s"，不带单位时为秒
+        duration: String,
+
+        /// 计时器名称，会显示在输出和通知中
+        #[arg(short, long)]
+        label: Option<String>,
+
+        /// 到时后使用内置播放器播放的音频文件
+        #[arg(short, long, value_name = "PATH")]
+        audio: Option<PathBuf>,
+
+        /// 到时后发送系统通知
+        #[arg(short, long)]
+        notify: bool,
+    },
+
+    /// 查看守护进程的运行状态
+    Status {
+        #[command(flatten)]
+        connection: Connection,
+    },
+
+    /// 查看守护进程下一次要执行的任务
+    Next {
+        #[command(flatten)]
+        connection: Connection,
+    },
+
+    /// 列出守护进程中的所有任务及其编号
+    List {
+        #[command(flatten)]
+        connection: Connection,
+    },
+
+    /// 暂停守护进程，暂停期间到达执行时间的任务将被跳过
+    Pause {
+        #[command(flatten)]
+        connection: Connection,
+    },
+
+    /// 恢复暂停的守护进程
+    Resume {
+        #[command(flatten)]
+        connection: Connection,
+    },
+
+    /// 让守护进程立即执行指定任务
+    Trigger {
+        /// 任务编号，可以使用 list 命令查看
+        item: String,
+
+        #[command(flatten)]
+        connection: Connection,
+    },
+
+    /// 让守护进程重新载入配置
+    Reload {
+        #[command(flatten)]
+        connection: Connection,
+    },
+
+    /// 让守护进程停止正在播放的声音
+    Silence {
+        #[command(flatten)]
+        connection: Connection,
+    },
+
+    /// 停止守护进程
+    Stop {
+        #[command(flatten)]
+        connection: Connection,
+    },
+
     /// 测试功能
     Test {
         #[command(subcommand)]
@@ -38,6 +169,13 @@ pub enum Actions {
     },
 }
 
+#[derive(Debug, clap::Args)]
+pub struct Connection {
+    /// 守护进程控制套接字的名称或路径，在 Windows 上为命名管道的名称
+    #[arg(long, value_name = "NAME", default_value = DEFAULT_SOCKET_NAME)]
+    pub socket: String,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Functions {
     /// 执行命令或打开文件
@@ -47,12 +185,40 @@ pub enum Functions {
 
         /// 要传递给命令的参数
         parameters: Option<Vec<String>>,
+
+        /// 使用指定的 Shell（如 sh、bash、cmd、powershell 或其路径）将命令作为脚本执行
+        #[arg(short, long, value_name = "SHELL")]
+        shell: Option<String>,
+
+        /// 等待命令结束并输出其退出状态和输出
+        #[arg(short, long)]
+        wait: bool,
     },
 
     /// 播放音频
     PlayAudio {
         /// 要播放的音频文件
         path: PathBuf,
+
+        /// 音量，1 为原始音量
+        #[arg(long)]
+        volume: Option<f32>,
+
+        /// 播放的遍数，0 为循环播放直到达到最长播放时间
+        #[arg(long = "loop", value_name = "TIMES")]
+        loops: Option<u32>,
+
+        /// 淡入时长（秒）
+        #[arg(long, value_name = "SECONDS")]
+        fade_in: Option<f64>,
+
+        /// 淡出时长（秒）
+        #[arg(long, value_name = "SECONDS")]
+        fade_out: Option<f64>,
+
+        /// 最长播放时间（秒），到达后自动停止
+        #[arg(long, value_name = "SECONDS")]
+        max_duration: Option<u64>,
     },
 
     /// 发送系统通知
@@ -70,3 +236,15 @@ pub enum Functions {
         second: u8,
     },
 }
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum MisfirePolicy {
+    /// 跳过
+    Skip,
+
+    /// 立即执行一次
+    RunOnce,
+
+    /// 在允许的延迟内立即执行，否则跳过
+    Grace,
+}
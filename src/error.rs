@@ -6,6 +6,12 @@ pub type DetailedResult<T = ()> = Result<NormalResult<T>, UnexpectedError>;
 pub enum UnexpectedError {
     Io(std::io::Error),
     SerializationAndDeserialization(serde_yaml::Error),
+    Cron(String, cron::error::Error),
+    Include(String, glob::PatternError),
+    Version(u32),
+    Listen(String, std::io::Error),
+    Connect(String, std::io::Error),
+    Format(std::path::PathBuf),
 }
 
 impl std::fmt::Display for UnexpectedError {
@@ -15,6 +21,34 @@ impl std::fmt::Display for UnexpectedError {
             UnexpectedError::SerializationAndDeserialization(e) => {
                 write!(f, "序列化和反序列化错误：{}", e)
             }
+            UnexpectedError::Cron(expression, e) => {
+                write!(f, "Cron 表达式 {} 解析错误：{}", expression, e)
+            }
+            UnexpectedError::Include(pattern, e) => {
+                write!(f, "包含的路径 {} 解析错误：{}", pattern, e)
+            }
+            UnexpectedError::Version(version) => write!(
+                f,
+                "配置文件版本 {} 高于程序支持的版本 {}，请更新程序",
+                version,
+                crate::structs::document::CURRENT_VERSION
+            ),
+            UnexpectedError::Listen(socket, e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+                write!(f, "控制套接字 {} 已被占用，可能已有守护进程在运行", socket)
+            }
+            UnexpectedError::Listen(socket, e) => {
+                write!(f, "无法监听控制套接字 {}：{}", socket, e)
+            }
+            UnexpectedError::Connect(socket, e) => write!(
+                f,
+                "无法连接到控制套接字 {}，请确认已使用 run --daemon 启动守护进程：{}",
+                socket, e
+            ),
+            UnexpectedError::Format(path) => write!(
+                f,
+                "无法识别配置文件 {} 的结构，为避免丢失其中的注释，没有修改该文件",
+                path.display()
+            ),
         }
     }
 }
@@ -39,6 +73,13 @@ pub enum NormalError {
     NumberFormat,
     Cancelled,
     Execution(opener::OpenError),
+    Spawn(std::io::Error),
+    ExitStatus(Option<i32>),
+    Timeout(u64),
+    PastTime(String),
+    Playlist(std::path::PathBuf, std::io::Error),
+    EmptyPlaylist(String),
+    SpeechEngine,
     Play(soloud::SoloudError),
     Notify(notify_rust::error::Error),
 }
@@ -50,6 +91,20 @@ impl std::fmt::Display for NormalError {
             NormalError::NumberFormat => write!(f, "数字格式错误"),
             NormalError::Cancelled => write!(f, "操作已取消"),
             NormalError::Execution(e) => write!(f, "命令执行错误：{}", e),
+            NormalError::Spawn(e) => write!(f, "命令启动错误：{}", e),
+            NormalError::ExitStatus(Some(code)) => write!(f, "命令以状态 {} 退出", code),
+            NormalError::ExitStatus(None) => write!(f, "命令被信号终止"),
+            NormalError::Timeout(seconds) => write!(f, "命令运行超过 {} 秒，已被终止", seconds),
+            NormalError::PastTime(time) => write!(f, "时间 {} 已经过去", time),
+            NormalError::Playlist(path, e) => {
+                write!(f, "播放列表 {} 读取错误：{}", path.display(), e)
+            }
+            NormalError::EmptyPlaylist(command) => {
+                write!(f, "播放列表 {} 中没有可以播放的文件", command)
+            }
+            NormalError::SpeechEngine => {
+                write!(f, "没有找到语音合成程序，请安装 espeak-ng 或 espeak")
+            }
             NormalError::Play(e) => write!(f, "音频播放错误：{}", e),
             NormalError::Notify(e) => write!(f, "通知发送错误：{}", e),
         }
@@ -64,6 +119,12 @@ impl From<opener::OpenError> for NormalError {
     }
 }
 
+impl From<std::io::Error> for NormalError {
+    fn from(e: std::io::Error) -> Self {
+        NormalError::Spawn(e)
+    }
+}
+
 impl From<soloud::SoloudError> for NormalError {
     fn from(e: soloud::SoloudError) -> Self {
         NormalError::Play(e)
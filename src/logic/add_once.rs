@@ -0,0 +1,160 @@
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Timelike};
+use std::path::Path;
+use std::{env, fs};
+
+use chrono_tz::Tz;
+
+use crate::error::NormalError::{Input, NumberFormat, PastTime};
+use crate::error::{DetailedResult, FinalResult, NormalResult, PrintingArgs, ResultPrinting};
+use crate::structs::config::{Command, Entry};
+use crate::structs::document::Document;
+use crate::structs::once::{once_path, OnceItem};
+use crate::utils::duration::parse_duration;
+
+pub fn add_once(config_path: &Path, at: &str, command: Command) -> FinalResult {
+    add(config_path, at, command)?.result_println(PrintingArgs::normal());
+
+    Ok(())
+}
+
+fn add(config_path: &Path, at: &str, command: Command) -> DetailedResult {
+    let now = Local::now()
+        .naive_local()
+        .with_nanosecond(0)
+        .unwrap_or_default();
+    let at = match parse_at(at, now) {
+        Ok(at) if at <= now => return Ok(Err(PastTime(at.to_string()))),
+        Ok(at) => at,
+        Err(e) => return Ok(Err(e)),
+    };
+
+    let path = once_path(config_path);
+    let mut document = match fs::read_to_string(&path) {
+        Ok(content) => Document::parse(&content)?.0,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Document::new(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    println!(
+        "将在 {} 执行 {}{}",
+        at.format("%Y-%m-%d %H:%M:%S"),
+        command.command,
+        command.arguments_note()
+    );
+
+    let (at, timezone) = match local_timezone(at) {
+        Some(timezone) => (at, timezone),
+        None => match Local.from_local_datetime(&at).earliest() {
+            Some(at) => (at.naive_utc(), Tz::UTC),
+            None => return Ok(Err(Input)),
+        },
+    };
+
+    document.items.push(Entry::Once(OnceItem {
+        name: None,
+        at,
+        timezone: Some(timezone),
+        tags: Vec::new(),
+        misfire: None,
+        sequential: false,
+        commands: vec![command],
+    }));
+    fs::write(&path, serde_yaml::to_string(&document)?)?;
+
+    println!("已写入 {}", path.display());
+
+    Ok(Ok(()))
+}
+
+fn local_timezone(at: NaiveDateTime) -> Option<Tz> {
+    let name = env::var("TZ")
+        .ok()
+        .or_else(|| iana_time_zone::get_timezone().ok())?;
+    let timezone: Tz = name.trim_start_matches(':').parse().ok()?;
+
+    let local = Local.from_local_datetime(&at).earliest()?;
+    let zoned = timezone.from_local_datetime(&at).earliest()?;
+    (local.fixed_offset() == zoned.fixed_offset()).then_some(timezone)
+}
+
+fn parse_at(at: &str, now: NaiveDateTime) -> NormalResult<NaiveDateTime> {
+    let at = at.trim();
+
+    if let Some(duration) = at.strip_prefix('+') {
+        let delta = TimeDelta::from_std(parse_duration(duration)?).map_err(|_| NumberFormat)?;
+
+        return now.checked_add_signed(delta).ok_or(NumberFormat);
+    }
+
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(at) = NaiveDateTime::parse_from_str(at, format) {
+            return Ok(at);
+        }
+    }
+
+    for format in ["%H:%M:%S", "%H:%M"] {
+        if let Ok(time) = NaiveTime::parse_from_str(at, format) {
+            let today = now.date().and_time(time);
+            return Ok(if today > now {
+                today
+            } else {
+                next_day(now.date()).and_time(time)
+            });
+        }
+    }
+
+    Err(Input)
+}
+
+fn next_day(date: NaiveDate) -> NaiveDate {
+    date.succ_opt().unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn parse_at_accepts_dates_times_and_durations() {
+        let now = at("2026-10-18 12:00:00");
+
+        assert_eq!(
+            parse_at("2026-10-20 14:30", now).unwrap(),
+            at("2026-10-20 14:30:00")
+        );
+        assert_eq!(
+            parse_at("2026-10-20T14:30:15", now).unwrap(),
+            at("2026-10-20 14:30:15")
+        );
+        assert_eq!(parse_at("14:30", now).unwrap(), at("2026-10-18 14:30:00"));
+        assert_eq!(
+            parse_at("11:59:59", now).unwrap(),
+            at("2026-10-19 11:59:59")
+        );
+        assert_eq!(parse_at("12:00", now).unwrap(), at("2026-10-19 12:00:00"));
+        assert_eq!(
+            parse_at(" +1h30m ", now).unwrap(),
+            at("2026-10-18 13:30:00")
+        );
+        assert_eq!(parse_at("+2d", now).unwrap(), at("2026-10-20 12:00:00"));
+    }
+
+    #[test]
+    fn parse_at_rejects_malformed_input() {
+        let now = at("2026-10-18 12:00:00");
+
+        assert!(matches!(parse_at("tomorrow", now), Err(Input)));
+        assert!(matches!(parse_at("25:00", now), Err(Input)));
+        assert!(matches!(parse_at("+5x", now), Err(Input)));
+        assert!(matches!(parse_at("+", now), Err(Input)));
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use soloud::{AudioExt, Handle, LoadExt, Soloud, Wav};
+
+use crate::error::NormalError::Cancelled;
+use crate::error::NormalResult;
+use crate::structs::audio_mode::AudioMode;
+use crate::structs::audio_options::AudioOptions;
+
+const VOICE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct Audio {
+    sender: Sender<Job>,
+    mode: Arc<Mutex<AudioMode>>,
+}
+
+pub enum Sound {
+    File(PathBuf),
+    Data(Vec<u8>),
+}
+
+enum Job {
+    Play(Playback),
+    Silence(Sender<usize>),
+}
+
+struct Playback {
+    sound: Sound,
+    options: AudioOptions,
+    done: Sender<NormalResult>,
+}
+
+struct Voice {
+    handle: Handle,
+    _wav: Wav,
+    fade_out_at: Option<Instant>,
+    fade_out: f64,
+    done: Sender<NormalResult>,
+}
+
+impl Audio {
+    pub fn new(mode: AudioMode) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mode = Arc::new(Mutex::new(mode));
+
+        let engine_mode = mode.clone();
+        thread::spawn(move || run_engine(receiver, engine_mode));
+
+        Audio { sender, mode }
+    }
+
+    pub fn set_mode(&self, mode: AudioMode) {
+        *self.mode.lock().unwrap_or_else(PoisonError::into_inner) = mode;
+    }
+
+    pub fn play(&self, sound: Sound, options: AudioOptions) -> NormalResult {
+        let (done, receiver) = mpsc::channel();
+        self.sender
+            .send(Job::Play(Playback {
+                sound,
+                options,
+                done,
+            }))
+            .map_err(|_| Cancelled)?;
+
+        receiver.recv().unwrap_or(Err(Cancelled))
+    }
+
+    pub fn silence(&self) -> usize {
+        let (done, receiver) = mpsc::channel();
+        if self.sender.send(Job::Silence(done)).is_err() {
+            return 0;
+        }
+
+        receiver.recv().unwrap_or_default()
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Audio::new(AudioMode::default())
+    }
+}
+
+fn run_engine(receiver: Receiver<Job>, mode: Arc<Mutex<AudioMode>>) {
+    let mut player: Option<Soloud> = None;
+    let mut playing: Vec<Voice> = Vec::new();
+    let mut queued: VecDeque<Playback> = VecDeque::new();
+
+    loop {
+        if let Some(player) = &player {
+            let now = Instant::now();
+            playing.retain_mut(|voice| {
+                if voice.fade_out_at.is_some_and(|at| at <= now) {
+                    voice.fade_out_at = None;
+                    player.fade_volume(voice.handle, 0.0, voice.fade_out);
+                    player.schedule_stop(voice.handle, voice.fade_out);
+                }
+
+                let active = player.is_valid_voice_handle(voice.handle);
+                if !active {
+                    let _ = voice.done.send(Ok(()));
+                }
+                active
+            });
+        }
+
+        let queue = *mode.lock().unwrap_or_else(PoisonError::into_inner) == AudioMode::Queue;
+        while !queue || playing.is_empty() {
+            let Some(playback) = queued.pop_front() else {
+                break;
+            };
+
+            match start(&mut player, &playback) {
+                Ok(voice) => playing.push(voice),
+                Err(e) => {
+                    let _ = playback.done.send(Err(e));
+                }
+            }
+        }
+
+        let job = if playing.is_empty() && queued.is_empty() {
+            match receiver.recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            }
+        } else {
+            match receiver.recv_timeout(VOICE_POLL_INTERVAL) {
+                Ok(job) => job,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) if playing.is_empty() => return,
+                Err(RecvTimeoutError::Disconnected) => {
+                    thread::sleep(VOICE_POLL_INTERVAL);
+                    continue;
+                }
+            }
+        };
+
+        match job {
+            Job::Play(playback) => queued.push_back(playback),
+            Job::Silence(done) => {
+                let count = playing.len() + queued.len();
+                for voice in playing.drain(..) {
+                    if let Some(player) = &player {
+                        player.stop(voice.handle);
+                    }
+                    let _ = voice.done.send(Ok(()));
+                }
+                for playback in queued.drain(..) {
+                    let _ = playback.done.send(Ok(()));
+                }
+                let _ = done.send(count);
+            }
+        }
+    }
+}
+
+fn start(player: &mut Option<Soloud>, playback: &Playback) -> NormalResult<Voice> {
+    let player = match player {
+        Some(player) => player,
+        None => player.insert(Soloud::default()?),
+    };
+    let options = playback.options;
+
+    let mut wav = Wav::default();
+    match &playback.sound {
+        Sound::File(path) => wav.load(path)?,
+        Sound::Data(data) => wav.load_mem(data)?,
+    }
+
+    let fade_in = options.fade_in();
+    let volume = if fade_in > 0.0 { 0.0 } else { options.volume() };
+    let handle = player.play_ex(&wav, volume, 0.0, false, Handle::PRIMARY);
+    if fade_in > 0.0 {
+        player.fade_volume(handle, options.volume(), fade_in);
+    }
+    if options.loops() != 1 {
+        player.set_looping(handle, true);
+    }
+
+    let fade_out = options.fade_out();
+    let fade_out_at = options.duration(wav.length()).map(|duration| {
+        let start = (duration - fade_out).max(0.0);
+        Instant::now() + Duration::from_secs_f64(start)
+    });
+
+    Ok(Voice {
+        handle,
+        _wav: wav,
+        fade_out_at,
+        fade_out,
+        done: playback.done.clone(),
+    })
+}
@@ -0,0 +1,225 @@
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use interprocess::local_socket::{
+    prelude::*, GenericFilePath, GenericNamespaced, Listener, ListenerOptions, Name, Stream,
+};
+
+use crate::error::{FinalResult, UnexpectedError};
+
+pub enum Message {
+    Reload,
+    Control(Request, Sender<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    Status,
+    Next,
+    List,
+    Pause,
+    Resume,
+    Trigger(String),
+    Reload,
+    Silence,
+    Stop,
+}
+
+impl Request {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let (name, argument) = line.split_once(' ').unwrap_or((line, ""));
+
+        match (name, argument.trim()) {
+            ("status", "") => Some(Request::Status),
+            ("next", "") => Some(Request::Next),
+            ("list", "") => Some(Request::List),
+            ("pause", "") => Some(Request::Pause),
+            ("resume", "") => Some(Request::Resume),
+            ("trigger", item) if !item.is_empty() => Some(Request::Trigger(item.to_string())),
+            ("reload", "") => Some(Request::Reload),
+            ("silence", "") => Some(Request::Silence),
+            ("stop", "") => Some(Request::Stop),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Request::Status => write!(f, "status"),
+            Request::Next => write!(f, "next"),
+            Request::List => write!(f, "list"),
+            Request::Pause => write!(f, "pause"),
+            Request::Resume => write!(f, "resume"),
+            Request::Trigger(item) => write!(f, "trigger {}", item),
+            Request::Reload => write!(f, "reload"),
+            Request::Silence => write!(f, "silence"),
+            Request::Stop => write!(f, "stop"),
+        }
+    }
+}
+
+pub fn listen(socket: &str, sender: Sender<Message>) -> FinalResult {
+    let listener =
+        create_listener(socket).map_err(|e| UnexpectedError::Listen(socket_display(socket), e))?;
+
+    println!("守护进程正在监听控制套接字 {}", socket_display(socket));
+
+    thread::spawn(move || {
+        for connection in listener.incoming().filter_map(Result::ok) {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let _ = respond(connection, &sender);
+            });
+        }
+    });
+
+    Ok(())
+}
+
+fn create_listener(socket: &str) -> io::Result<Listener> {
+    let options = || -> io::Result<ListenerOptions<'static>> {
+        let options = ListenerOptions::new().name(socket_name(socket)?);
+        #[cfg(unix)]
+        let options = {
+            use interprocess::os::unix::local_socket::ListenerOptionsExt;
+            options.mode(0o600)
+        };
+
+        Ok(options)
+    };
+
+    match options()?.create_sync() {
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse => match socket_path(socket) {
+            Some(path) if socket_name(socket).and_then(Stream::connect).is_err() => {
+                fs::remove_file(path)?;
+                options()?.create_sync()
+            }
+            _ => Err(e),
+        },
+        result => result,
+    }
+}
+
+fn respond(connection: Stream, sender: &Sender<Message>) -> io::Result<()> {
+    let mut reader = BufReader::new(connection);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let response = match Request::parse(&line) {
+        Some(request) => {
+            let (reply_sender, reply_receiver) = mpsc::channel();
+            let _ = sender.send(Message::Control(request, reply_sender));
+            reply_receiver
+                .recv()
+                .unwrap_or_else(|_| "守护进程没有响应".to_string())
+        }
+        None => format!("无法识别的请求 {}", line.trim()),
+    };
+
+    reader.get_mut().write_all(response.as_bytes())?;
+    reader.get_mut().write_all(b"\n")
+}
+
+pub fn control(socket: &str, request: Request) -> FinalResult {
+    let connection = socket_name(socket)
+        .and_then(Stream::connect)
+        .map_err(|e| UnexpectedError::Connect(socket_display(socket), e))?;
+
+    let mut reader = BufReader::new(connection);
+    reader
+        .get_mut()
+        .write_all(format!("{}\n", request).as_bytes())?;
+
+    let mut response = String::new();
+    reader.read_to_string(&mut response)?;
+    print!("{}", response);
+
+    Ok(())
+}
+
+fn socket_name(socket: &str) -> io::Result<Name<'static>> {
+    match socket_path(socket) {
+        Some(path) => path.to_fs_name::<GenericFilePath>().map(Name::into_owned),
+        None => format!("{}-{}", env::var("USERNAME").unwrap_or_default(), socket)
+            .to_ns_name::<GenericNamespaced>()
+            .map(Name::into_owned),
+    }
+}
+
+fn socket_path(socket: &str) -> Option<PathBuf> {
+    if socket.contains(['/', '\\']) {
+        Some(PathBuf::from(socket))
+    } else {
+        runtime_path(socket)
+    }
+}
+
+#[cfg(unix)]
+fn runtime_path(socket: &str) -> Option<PathBuf> {
+    Some(
+        match env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir).join(socket),
+            None => env::temp_dir().join(format!("{}-{}", unsafe { libc::getuid() }, socket)),
+        },
+    )
+}
+
+#[cfg(not(unix))]
+fn runtime_path(_: &str) -> Option<PathBuf> {
+    None
+}
+
+fn socket_display(socket: &str) -> String {
+    socket_path(socket).map_or_else(|| socket.to_string(), |path| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_round_trip_through_text() {
+        for request in [
+            Request::Status,
+            Request::Next,
+            Request::List,
+            Request::Pause,
+            Request::Resume,
+            Request::Trigger("3".to_string()),
+            Request::Reload,
+            Request::Silence,
+            Request::Stop,
+        ] {
+            assert_eq!(Request::parse(&format!("{}\n", request)), Some(request));
+        }
+    }
+
+    #[test]
+    fn parse_rejects_malformed_requests() {
+        assert_eq!(
+            Request::parse("  trigger   12 \r\n"),
+            Some(Request::Trigger("12".to_string()))
+        );
+        assert_eq!(Request::parse("trigger"), None);
+        assert_eq!(Request::parse("status now"), None);
+        assert_eq!(Request::parse("restart"), None);
+        assert_eq!(Request::parse(""), None);
+    }
+
+    #[test]
+    fn socket_paths_are_used_as_given() {
+        assert_eq!(
+            socket_path("/tmp/boom.sock"),
+            Some(PathBuf::from("/tmp/boom.sock"))
+        );
+        assert_eq!(socket_display("/tmp/boom.sock"), "/tmp/boom.sock");
+    }
+}
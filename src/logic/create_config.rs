@@ -5,7 +5,9 @@ use std::{fs, io};
 
 use crate::error::NormalError::{Cancelled, Input, NumberFormat};
 use crate::error::{DetailedResult, FinalResult, PrintingArgs, ResultPrinting};
-use crate::structs::config::{AddCommand, Command, Item, Time};
+use crate::structs::config::{AddCommand, Command, Entry, Item, Time};
+use crate::structs::document::Document;
+use crate::utils::arguments::split_arguments;
 use crate::utils::stdio::print_and_readln;
 
 pub fn create_config(config_path: &PathBuf) -> FinalResult {
@@ -111,10 +113,21 @@ fn create_with_all_parameters(config_path: &PathBuf) -> DetailedResult {
             continue;
         }
 
-        config.add_command(time, command);
+        config.add_command(
+            Item {
+                time,
+                ..Default::default()
+            },
+            command,
+        );
     }
 
-    fs::write(config_path, serde_yaml::to_string(&config)?)?;
+    fs::write(
+        config_path,
+        serde_yaml::to_string(&Document::new(
+            config.into_iter().map(Entry::Time).collect(),
+        ))?,
+    )?;
 
     Ok(Ok(()))
 }
@@ -148,7 +161,7 @@ fn parse_item_with_all_parameters(input: SplitWhitespace) -> DetailedResult<(Tim
         None => return Ok(Err(Input)),
     }
 
-    command.parameters = input.collect::<Vec<_>>().join(" ");
+    command.args = input.map(|argument| argument.to_string()).collect();
 
     Ok(Ok((time, command)))
 }
@@ -184,10 +197,21 @@ fn create_config_by_interactive(config_path: &PathBuf) -> DetailedResult {
             continue;
         }
 
-        config.add_command(time, command);
+        config.add_command(
+            Item {
+                time,
+                ..Default::default()
+            },
+            command,
+        );
     }
 
-    fs::write(config_path, serde_yaml::to_string(&config)?)?;
+    fs::write(
+        config_path,
+        serde_yaml::to_string(&Document::new(
+            config.into_iter().map(Entry::Time).collect(),
+        ))?,
+    )?;
 
     Ok(Ok(()))
 }
@@ -209,7 +233,7 @@ fn parse_item_by_interactive() -> DetailedResult<(Time, Command)> {
                 input
             }
         },
-        parameters: print_and_readln("请输入参数（可选）：")?,
+        args: split_arguments(&print_and_readln("请输入参数（可选）：")?),
         audio: print_and_readln("是否使用内置播放器播放音频？（Y/n）")?
             .to_lowercase()
             .as_str()
@@ -218,6 +242,7 @@ fn parse_item_by_interactive() -> DetailedResult<(Time, Command)> {
             Ok(n) => n,
             Err(e) => return Ok(Err(e)),
         },
+        ..Default::default()
     };
 
     Ok(Ok((time, command)))
@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+
+use crate::error::NormalError::Cancelled;
+use crate::error::{NormalResult, PrintingArgs, ResultPrinting};
+use crate::logic::audio::{Audio, Sound};
+use crate::logic::functions::{
+    execute, send_failure_notification, send_notification, synthesize_speech,
+};
+use crate::logic::processes::Processes;
+use crate::structs::config::Command;
+use crate::structs::playlist::PlaylistOrder;
+use crate::structs::retry::Retry;
+
+pub fn dispatch(
+    commands: Vec<Command>,
+    sequential: bool,
+    log: Option<PathBuf>,
+    processes: Arc<Processes>,
+    audio: Arc<Audio>,
+) {
+    if sequential {
+        thread::spawn(move || {
+            for command in &commands {
+                let reason = match run_chain(command, true, log.as_deref(), &processes, &audio) {
+                    Some(true) => continue,
+                    Some(false) => "执行失败",
+                    None => "没有执行完成",
+                };
+
+                println!("命令 {} {}，停止执行后续命令", command.name(), reason);
+                println!();
+                break;
+            }
+        });
+    } else {
+        for command in commands {
+            let log = log.clone();
+            let processes = processes.clone();
+            let audio = audio.clone();
+            thread::spawn(move || {
+                let wait = !command.on_success.is_empty() || !command.on_failure.is_empty();
+                run_chain(&command, wait, log.as_deref(), &processes, &audio);
+            });
+        }
+    }
+}
+
+fn run_chain(
+    command: &Command,
+    wait: bool,
+    log: Option<&Path>,
+    processes: &Processes,
+    audio: &Audio,
+) -> Option<bool> {
+    let succeeded = run_command(command, wait, log, processes, audio)?;
+
+    let follow_ups = if succeeded {
+        &command.on_success
+    } else {
+        &command.on_failure
+    };
+    for follow_up in follow_ups {
+        let wait = !follow_up.on_success.is_empty() || !follow_up.on_failure.is_empty();
+        run_chain(follow_up, wait, log, processes, audio);
+    }
+
+    Some(succeeded)
+}
+
+fn run_command(
+    command: &Command,
+    wait: bool,
+    log: Option<&Path>,
+    processes: &Processes,
+    audio: &Audio,
+) -> Option<bool> {
+    let retry = command.retry.unwrap_or(Retry {
+        attempts: 0,
+        backoff: 0,
+    });
+    let mut retried = 0;
+
+    loop {
+        let (result, message) = attempt(command, wait, log, processes, audio);
+        if matches!(result, Err(Cancelled)) {
+            return None;
+        }
+        result.result_println(PrintingArgs::customized(message));
+
+        let Err(e) = result else {
+            return Some(true);
+        };
+
+        if retried < retry.attempts {
+            let delay = retry.delay(retried);
+            retried += 1;
+            println!(
+                "将在 {} 秒后第 {} 次重试命令 {}",
+                delay.as_secs(),
+                retried,
+                command.name()
+            );
+            println!();
+            thread::sleep(delay);
+            continue;
+        }
+
+        if command.retry.is_some() {
+            println!("命令 {} 尝试了 {} 次仍然失败", command.name(), retried + 1);
+            println!();
+            send_failure_notification(command.name(), retried + 1, &e.to_string())
+                .result_println(PrintingArgs::customized("发送通知时遇到了问题"));
+        }
+
+        return Some(false);
+    }
+}
+
+fn attempt(
+    command: &Command,
+    wait: bool,
+    log: Option<&Path>,
+    processes: &Processes,
+    audio: &Audio,
+) -> (NormalResult, &'static str) {
+    if command.notify == -2 {
+        println!(
+            "为命令 {}{}发送通知{}",
+            command.name(),
+            if command.arguments().is_empty() {
+                " ".to_string()
+            } else {
+                command.arguments_note()
+            },
+            command.source_note()
+        );
+        println!();
+
+        (send_notification(command.name()), "发送通知时遇到了问题")
+    } else if let Some(text) = &command.speak {
+        println!("朗读 {}{}", text, command.source_note());
+        println!();
+
+        (
+            synthesize_speech(text, command.voice.as_deref())
+                .and_then(|data| audio.play(Sound::Data(data), command.audio_options)),
+            "朗读时遇到了问题",
+        )
+    } else if let Some(tone) = &command.tone {
+        println!("播放提示音 {}{}", tone, command.source_note());
+        println!();
+
+        (
+            audio.play(Sound::Data(tone.wav()), command.audio_options),
+            "播放提示音时遇到了问题",
+        )
+    } else if command.audio {
+        (play_playlist(command, audio), "播放音频时遇到了问题")
+    } else {
+        println!(
+            "执行命令 {}{}{}",
+            command.command,
+            command.arguments_note(),
+            command.source_note()
+        );
+        println!();
+
+        let command = Command {
+            wait: command.wait || (wait && command.spawns_process()),
+            ..command.clone()
+        };
+
+        let slot = if command.spawns_process() {
+            match processes.acquire(&command) {
+                Some(slot) => Some(slot),
+                None => {
+                    println!(
+                        "上一次运行的命令 {} 仍在运行，跳过本次运行",
+                        command.command
+                    );
+                    println!();
+                    return (Err(Cancelled), "执行命令时遇到了问题");
+                }
+            }
+        } else {
+            None
+        };
+
+        (
+            execute(
+                &command,
+                log,
+                slot.as_ref().map(|slot| slot.cancel.as_ref()),
+            ),
+            "执行命令时遇到了问题",
+        )
+    }
+}
+
+fn play_playlist(command: &Command, audio: &Audio) -> NormalResult {
+    if command.is_single_track() {
+        println!("播放音频 {}{}", command.command, command.source_note());
+        println!();
+
+        return audio.play(
+            Sound::File(PathBuf::from(&command.command)),
+            command.audio_options,
+        );
+    }
+
+    let tracks = command.order.select(command.playlist()?);
+    let count = tracks.len();
+    for (index, track) in tracks.into_iter().enumerate() {
+        match command.order {
+            PlaylistOrder::Random => println!(
+                "播放音频 {}{}，随机选择了 {}",
+                command.command,
+                command.source_note(),
+                track.display()
+            ),
+            _ => println!(
+                "播放音频 {}{}，第 {}/{} 首：{}",
+                command.command,
+                command.source_note(),
+                index + 1,
+                count,
+                track.display()
+            ),
+        }
+        println!();
+
+        audio.play(Sound::File(track), command.audio_options)?;
+    }
+
+    Ok(())
+}
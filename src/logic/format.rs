@@ -0,0 +1,234 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{FinalResult, UnexpectedError};
+use crate::structs::config::Entry;
+use crate::structs::document::Document;
+
+struct Chunk {
+    comments: Vec<String>,
+    content: String,
+}
+
+pub fn format(config_path: &PathBuf) -> FinalResult {
+    let original = fs::read_to_string(config_path)?;
+    let (document, legacy) = Document::parse(&original)?;
+
+    if legacy {
+        println!("配置文件使用旧版格式，将升级为新版格式");
+    }
+
+    let formatted = match format_with_comments(&original, &document, legacy) {
+        Some(formatted) => formatted,
+        None if original.lines().any(|line| split_comment(line).1.is_some()) => {
+            return Err(UnexpectedError::Format(config_path.clone()));
+        }
+        None => {
+            let mut document = document;
+            document.items.sort_by_key(sort_key);
+            serde_yaml::to_string(&document)?
+        }
+    };
+
+    if formatted == original {
+        println!("配置文件已经是规范格式");
+    } else {
+        fs::write(config_path, formatted)?;
+        println!("已格式化配置文件 {}", config_path.display());
+    }
+
+    Ok(())
+}
+
+fn format_with_comments(original: &str, document: &Document, legacy: bool) -> Option<String> {
+    let mut preamble = Vec::new();
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut pending = Vec::new();
+    let mut in_items = legacy;
+    let mut indent = None;
+
+    for line in original.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() || trimmed == "---" {
+            if chunks.is_empty() {
+                preamble.append(&mut pending);
+            }
+            continue;
+        }
+
+        if trimmed.starts_with('#') {
+            pending.push(trimmed.to_string());
+            continue;
+        }
+
+        if !legacy && !line.starts_with([' ', '-']) {
+            in_items = line.starts_with("items:");
+            indent = None;
+            preamble.append(&mut pending);
+            continue;
+        }
+
+        if !in_items {
+            continue;
+        }
+
+        let depth = line.len() - trimmed.len();
+        let indent = *indent.get_or_insert(depth);
+        if depth < indent {
+            return None;
+        }
+
+        if depth == indent && trimmed.starts_with('-') {
+            chunks.push(Chunk {
+                comments: std::mem::take(&mut pending),
+                content: String::new(),
+            });
+        }
+
+        let chunk = chunks.last_mut()?;
+        let (content, comment) = split_comment(&line[indent..]);
+        chunk.comments.append(&mut pending);
+        if let Some(comment) = comment {
+            chunk.comments.push(comment.to_string());
+        }
+        chunk.content.push_str(content.trim_end());
+        chunk.content.push('\n');
+    }
+
+    match chunks.last_mut() {
+        Some(chunk) => chunk.comments.append(&mut pending),
+        None => preamble.append(&mut pending),
+    }
+
+    let mut parsed = Vec::new();
+    for chunk in chunks {
+        let mut entries = serde_yaml::from_str::<Vec<Entry>>(&chunk.content).ok()?;
+        if entries.len() != 1 {
+            return None;
+        }
+        parsed.push((chunk.comments, entries.remove(0)));
+    }
+
+    if parsed.len() != document.items.len() {
+        return None;
+    }
+
+    parsed.sort_by_key(|(_, entry)| sort_key(entry));
+
+    let mut formatted = String::new();
+    for comment in &preamble {
+        formatted.push_str(comment);
+        formatted.push('\n');
+    }
+    formatted.push_str(
+        &serde_yaml::to_string(&Document {
+            items: Vec::new(),
+            ..document.clone()
+        })
+        .ok()?,
+    );
+    if !parsed.is_empty() {
+        formatted.push_str("items:\n");
+    }
+    for (comments, entry) in parsed {
+        for comment in comments {
+            formatted.push_str(&comment);
+            formatted.push('\n');
+        }
+        formatted.push_str(&serde_yaml::to_string(&[entry]).ok()?);
+    }
+
+    Some(formatted)
+}
+
+fn sort_key(entry: &Entry) -> (u8, String) {
+    match entry {
+        Entry::Calendar(_) | Entry::Include(_) => (0, String::new()),
+        Entry::Time(item) => (1, item.time.to_string()),
+        Entry::Cron(item) => (2, item.cron.clone()),
+        Entry::Once(item) => (3, item.at.to_string()),
+    }
+}
+
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut quote = None;
+    let mut previous = ' ';
+
+    for (index, char) in line.char_indices() {
+        match (quote, char) {
+            (None, '\'' | '"') => quote = Some(char),
+            (Some(q), _) if q == char => quote = None,
+            (None, '#') if previous.is_whitespace() => {
+                return (&line[..index], Some(&line[index..]));
+            }
+            _ => {}
+        }
+        previous = char;
+    }
+
+    (line, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(original: &str) -> Option<String> {
+        let (document, legacy) = Document::parse(original).unwrap();
+        format_with_comments(original, &document, legacy)
+    }
+
+    #[test]
+    fn sorts_indented_items_and_keeps_comments() {
+        let formatted = format(
+            "# header
+version: 1
+items:
+  # evening
+  - time: { hour: 20, minute: 0, second: 0 }
+    commands: [{ command: b }] # trailing
+  # morning
+  - time: { hour: 8, minute: 0, second: 0 }
+    commands: [{ command: a }]
+",
+        )
+        .unwrap();
+
+        let position = |text| formatted.find(text).unwrap();
+        assert!(position("# header") < position("version: 1"));
+        assert!(position("# morning") < position("command: a"));
+        assert!(position("command: a") < position("# evening"));
+        assert!(position("# evening") < position("# trailing"));
+        assert!(position("# trailing") < position("command: b"));
+        assert_eq!(format(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn upgrades_legacy_lists() {
+        let formatted = format(
+            "# legacy
+
+# morning
+- time: { hour: 9, minute: 0, second: 0 }
+  commands: [{ command: a }]
+",
+        )
+        .unwrap();
+
+        assert!(formatted.starts_with("# legacy\nversion: 1\n"));
+        assert!(formatted.contains("items:\n# morning\n- time:"));
+    }
+
+    #[test]
+    fn gives_up_on_flow_style_items() {
+        assert_eq!(
+            format(
+                "version: 1 # keep
+items: [{ time: { hour: 9, minute: 0, second: 0 }, commands: [{ command: a }] }]
+"
+            ),
+            None
+        );
+    }
+}
@@ -1,45 +1,296 @@
-use chrono::{Local, Timelike};
-use std::path::PathBuf;
-use std::thread;
+use chrono::{DateTime, Local, TimeDelta, Timelike};
+use std::io::{stdout, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use std::{process, thread};
 
 use notify_rust::Notification;
 use opener::open;
-use soloud::{AudioExt, LoadExt, Soloud, Wav};
 
-use crate::error::NormalResult;
-use crate::structs::config::Time;
+use crate::error::NormalError::{Cancelled, ExitStatus, NumberFormat, SpeechEngine, Timeout};
+use crate::error::{NormalResult, PrintingArgs, ResultPrinting};
+use crate::logic::audio::{Audio, Sound};
+use crate::logic::process_tree::ProcessTree;
+use crate::structs::audio_options::AudioOptions;
+use crate::structs::config::{Command, Item, Time};
+use crate::structs::execution::Execution;
+use crate::utils::duration::{format_duration, parse_duration};
 use crate::APP_NAME;
 
-pub fn execute(command: &String, parameters: Option<Vec<String>>) -> NormalResult {
-    open(command, parameters.unwrap_or_default().join(" "))?;
+const WAIT_SLICE: Duration = Duration::from_secs(1);
+const CLOCK_CHANGE_THRESHOLD_SECONDS: i64 = 2;
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const PIPE_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+const SPEECH_PROGRAMS: [&str; 2] = ["espeak-ng", "espeak"];
+
+pub fn execute(command: &Command, log: Option<&Path>, cancel: Option<&AtomicBool>) -> NormalResult {
+    if !command.spawns_process() {
+        if cfg!(windows) || command.parameters.is_empty() {
+            open(&command.command, &command.parameters)?;
+        } else {
+            process_command(command).spawn()?;
+        }
+        return Ok(());
+    }
+
+    let not_cancelled = AtomicBool::new(false);
+    let Some(cancel) = cancel.or(command.wait.then_some(&not_cancelled)) else {
+        process_command(command).spawn()?;
+        return Ok(());
+    };
+
+    let execution = execute_and_wait(command, cancel)?;
+    if execution.cancelled {
+        return Err(Cancelled);
+    }
+
+    if command.wait {
+        match log {
+            Some(log) => execution
+                .append_to(log)
+                .result_println(PrintingArgs::customized("写入日志时遇到了问题")),
+            None => print!("{}", execution),
+        }
+
+        return match (execution.timed_out, command.timeout) {
+            (true, Some(timeout)) => Err(Timeout(timeout)),
+            _ if execution.succeeded() => Ok(()),
+            _ => Err(ExitStatus(execution.code)),
+        };
+    }
 
     Ok(())
 }
 
-pub fn play_audio(path: PathBuf) -> NormalResult {
-    let player = Soloud::default()?;
-    let mut wav = Wav::default();
-    wav.load(path)?;
-    player.play(&wav);
+fn process_command(command: &Command) -> process::Command {
+    let mut process = match &command.shell {
+        Some(shell) => {
+            let mut process = process::Command::new(shell);
+            process.args(shell_arguments(shell)).arg(&command.command);
+            process
+        }
+        None => {
+            let mut process = process::Command::new(&command.command);
+            process.args(command.arguments());
+            process
+        }
+    };
+    process.envs(&command.env);
+    if let Some(working_dir) = &command.working_dir {
+        process.current_dir(working_dir);
+    }
 
-    while player.active_voice_count() > 0 {}
+    process
+}
 
-    Ok(())
+fn shell_arguments(shell: &str) -> &'static [&'static str] {
+    let name = Path::new(shell)
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match name.as_str() {
+        "cmd" => &["/C"],
+        "powershell" | "pwsh" => &["-NoProfile", "-Command"],
+        _ => &["-c"],
+    }
+}
+
+fn execute_and_wait(command: &Command, cancel: &AtomicBool) -> NormalResult<Execution> {
+    let started = Local::now();
+    let instant = Instant::now();
+
+    let mut process = process_command(command);
+    if command.wait {
+        process
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+    }
+    let mut tree = ProcessTree::spawn(&mut process)?;
+    let stdout = read_pipe(tree.child().stdout.take());
+    let stderr = read_pipe(tree.child().stderr.take());
+
+    let timeout = command.timeout.map(Duration::from_secs);
+    let mut timed_out = false;
+    let mut cancelled = false;
+    let status = loop {
+        if let Some(status) = tree.child().try_wait()? {
+            break status;
+        }
+
+        if timeout.is_some_and(|timeout| instant.elapsed() >= timeout) {
+            timed_out = true;
+            break tree.kill()?;
+        }
+
+        if cancel.load(Ordering::Relaxed) {
+            cancelled = true;
+            break tree.kill()?;
+        }
+
+        thread::sleep(PROCESS_POLL_INTERVAL);
+    };
+
+    let drained = Instant::now() + PIPE_DRAIN_TIMEOUT;
+    Ok(Execution {
+        command: command.clone(),
+        started,
+        duration: instant.elapsed(),
+        code: status.code(),
+        timed_out,
+        cancelled,
+        stdout: stdout.collect(drained),
+        stderr: stderr.collect(drained),
+    })
+}
+
+struct Pipe {
+    output: Arc<Mutex<Vec<u8>>>,
+    done: Receiver<()>,
+}
+
+impl Pipe {
+    fn collect(self, deadline: Instant) -> String {
+        let _ = self
+            .done
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()));
+        let output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+
+        String::from_utf8_lossy(&output).into_owned()
+    }
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> Pipe {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let (sender, done) = mpsc::channel();
+
+    let shared = Arc::clone(&output);
+    thread::spawn(move || {
+        if let Some(mut pipe) = pipe {
+            let mut buffer = [0; 4096];
+            while let Ok(length @ 1..) = pipe.read(&mut buffer) {
+                shared
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .extend_from_slice(&buffer[..length]);
+            }
+        }
+        let _ = sender.send(());
+    });
+
+    Pipe { output, done }
+}
+
+pub fn synthesize_speech(text: &str, voice: Option<&str>) -> NormalResult<Vec<u8>> {
+    for program in SPEECH_PROGRAMS {
+        let mut process = process::Command::new(program);
+        process.arg("--stdout");
+        if let Some(voice) = voice {
+            process.args(["-v", voice]);
+        }
+
+        let mut child = match process
+            .arg("--stdin")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            result => result?,
+        };
+
+        let stdin = child.stdin.take();
+        let text = text.to_string();
+        let writer = thread::spawn(move || {
+            if let Some(mut stdin) = stdin {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+        });
+        let output = child.wait_with_output()?;
+        let _ = writer.join();
+        if !output.status.success() {
+            return Err(ExitStatus(output.status.code()));
+        }
+
+        return Ok(output.stdout);
+    }
+
+    Err(SpeechEngine)
 }
 
 pub fn send_notification(command: &str) -> NormalResult {
+    show_notification(
+        "任务提醒",
+        &format!("你为命令 {} 设置的提醒触发了", command),
+    )
+}
+
+pub fn send_failure_notification(command: &str, attempts: u32, error: &str) -> NormalResult {
+    show_notification(
+        "任务失败",
+        &format!("命令 {} 尝试了 {} 次仍然失败：{}", command, attempts, error),
+    )
+}
+
+fn show_notification(summary: &str, body: &str) -> NormalResult {
     Notification::new()
         .appname(APP_NAME)
-        .summary("任务提醒")
-        .body(&format!(
-            "你为命令 {} 设置的提醒触发了\n来自 {}",
-            command, APP_NAME
-        ))
+        .summary(summary)
+        .body(&format!("{}\n来自 {}", body, APP_NAME))
         .show()?;
 
     Ok(())
 }
 
+pub fn timer(
+    duration: &str,
+    label: Option<&str>,
+    audio: Option<PathBuf>,
+    notify: bool,
+) -> NormalResult {
+    let duration = parse_duration(duration)?;
+    let label = label.unwrap_or("计时器");
+    let deadline = Local::now() + TimeDelta::from_std(duration).map_err(|_| NumberFormat)?;
+
+    println!(
+        "{} 开始计时 {}，将在 {} 结束",
+        label,
+        format_duration(duration),
+        deadline.format("%H:%M:%S")
+    );
+
+    loop {
+        let remaining = (deadline - Local::now()).to_std().unwrap_or_default();
+        let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+
+        print!("\r剩余 {}", format_duration(Duration::from_secs(seconds)));
+        let _ = stdout().flush();
+
+        if seconds == 0 {
+            break;
+        }
+        thread::sleep(remaining - Duration::from_secs(seconds - 1));
+    }
+
+    println!();
+    println!("{} 时间到", label);
+
+    if notify {
+        show_notification("计时结束", &format!("{} 时间到", label))?;
+    }
+    if let Some(audio) = audio {
+        Audio::default().play(Sound::File(audio), AudioOptions::default())?;
+    }
+
+    Ok(())
+}
+
 pub fn time(hour: u8, minute: u8, second: u8) -> NormalResult {
     let now = Local::now();
 
@@ -51,12 +302,17 @@ pub fn time(hour: u8, minute: u8, second: u8) -> NormalResult {
         now.nanosecond() / 1_000_000
     );
 
-    let duration = Time {
-        hour,
-        minute,
-        second,
+    let deadline = Item {
+        time: Time {
+            hour,
+            minute,
+            second,
+        },
+        ..Default::default()
     }
-    .duration_from(Local::now());
+    .next_after(now, false)
+    .ok_or(NumberFormat)?;
+    let duration = (deadline - now).to_std().unwrap_or_default();
 
     println!(
         "等待 {} 时 {} 分 {} 秒 {} 毫秒",
@@ -66,7 +322,7 @@ pub fn time(hour: u8, minute: u8, second: u8) -> NormalResult {
         duration.subsec_millis()
     );
 
-    thread::sleep(duration);
+    wait_until(deadline);
 
     let now = Local::now();
 
@@ -80,3 +336,40 @@ pub fn time(hour: u8, minute: u8, second: u8) -> NormalResult {
 
     Ok(())
 }
+
+pub fn wait_until(deadline: DateTime<Local>) {
+    let (_sender, receiver) = mpsc::channel::<()>();
+    wait_until_or(deadline, &receiver);
+}
+
+pub fn wait_until_or<T>(deadline: DateTime<Local>, receiver: &Receiver<T>) -> Option<T> {
+    let mut last = (Instant::now(), Local::now());
+
+    loop {
+        let instant = Instant::now();
+        let now = Local::now();
+
+        let drift = (now - last.1) - TimeDelta::from_std(instant - last.0).unwrap_or_default();
+        if drift.num_seconds().abs() >= CLOCK_CHANGE_THRESHOLD_SECONDS {
+            println!(
+                "检测到系统时间变化了 {} 秒，已重新计算等待时间",
+                drift.num_seconds()
+            );
+        }
+
+        if now >= deadline {
+            return None;
+        }
+
+        let timeout = (deadline - now)
+            .to_std()
+            .unwrap_or_default()
+            .min(WAIT_SLICE);
+        match receiver.recv_timeout(timeout) {
+            Ok(message) => return Some(message),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(timeout),
+        }
+        last = (instant, now);
+    }
+}
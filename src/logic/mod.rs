@@ -1,3 +1,10 @@
+pub mod add_once;
+pub mod audio;
+pub mod control;
 pub mod create_config;
+pub mod dispatch;
+pub mod format;
 pub mod functions;
+pub mod process_tree;
+pub mod processes;
 pub mod run;
@@ -0,0 +1,67 @@
+use std::io;
+use std::process::{self, Child, ExitStatus};
+
+pub struct ProcessTree {
+    child: Child,
+    #[cfg(windows)]
+    job: windows_sys::Win32::Foundation::HANDLE,
+}
+
+impl ProcessTree {
+    #[cfg(unix)]
+    pub fn spawn(process: &mut process::Command) -> io::Result<Self> {
+        use std::os::unix::process::CommandExt;
+
+        let child = process.process_group(0).spawn()?;
+
+        Ok(ProcessTree { child })
+    }
+
+    #[cfg(windows)]
+    pub fn spawn(process: &mut process::Command) -> io::Result<Self> {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::System::JobObjects::{AssignProcessToJobObject, CreateJobObjectW};
+
+        let child = process.spawn()?;
+        let job = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+        if !job.is_null() {
+            unsafe { AssignProcessToJobObject(job, child.as_raw_handle()) };
+        }
+
+        Ok(ProcessTree { child, job })
+    }
+
+    pub fn child(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    #[cfg(unix)]
+    pub fn kill(&mut self) -> io::Result<ExitStatus> {
+        let group = self.child.id() as libc::pid_t;
+        if unsafe { libc::kill(-group, libc::SIGKILL) } != 0 {
+            self.child.kill()?;
+        }
+
+        self.child.wait()
+    }
+
+    #[cfg(windows)]
+    pub fn kill(&mut self) -> io::Result<ExitStatus> {
+        use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+
+        if self.job.is_null() || unsafe { TerminateJobObject(self.job, 1) } == 0 {
+            self.child.kill()?;
+        }
+
+        self.child.wait()
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        if !self.job.is_null() {
+            unsafe { windows_sys::Win32::Foundation::CloseHandle(self.job) };
+        }
+    }
+}
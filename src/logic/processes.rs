@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::structs::config::Command;
+use crate::structs::overlap::Overlap;
+
+#[derive(Debug, Default)]
+pub struct Processes {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    running: HashMap<u64, Running>,
+    next_id: u64,
+    limit: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Running {
+    key: String,
+    cancel: Arc<AtomicBool>,
+}
+
+pub struct Slot<'a> {
+    processes: &'a Processes,
+    id: u64,
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl Processes {
+    pub fn set_limit(&self, limit: Option<usize>) {
+        self.lock().limit = limit;
+        self.changed.notify_all();
+    }
+
+    pub fn running(&self) -> usize {
+        self.lock().running.len()
+    }
+
+    pub fn acquire(&self, command: &Command) -> Option<Slot<'_>> {
+        let key = command.process_key();
+        let mut state = self.lock();
+
+        if state.is_running(&key) {
+            match command.overlap {
+                Overlap::Allow => {}
+                Overlap::Skip => return None,
+                Overlap::Queue => {
+                    println!("上一次运行的命令 {} 仍在运行，等待其结束", command.command);
+                    state = self.wait_while(state, |state| state.is_running(&key));
+                }
+                Overlap::KillPrevious => {
+                    println!("终止上一次运行的命令 {}", command.command);
+                    state
+                        .running
+                        .values()
+                        .filter(|running| running.key == key)
+                        .for_each(|running| running.cancel.store(true, Ordering::Relaxed));
+                    state = self.wait_while(state, |state| state.is_running(&key));
+                }
+            }
+        }
+
+        if state.is_full() {
+            println!(
+                "同时运行的命令已达到上限 {} 个，命令 {} 将等待其他命令结束",
+                state.limit.unwrap_or_default(),
+                command.command
+            );
+            state = self.wait_while(state, State::is_full);
+        }
+
+        let id = state.next_id;
+        let cancel = Arc::new(AtomicBool::new(false));
+        state.next_id += 1;
+        state.running.insert(
+            id,
+            Running {
+                key,
+                cancel: cancel.clone(),
+            },
+        );
+
+        Some(Slot {
+            processes: self,
+            id,
+            cancel,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn wait_while<'a>(
+        &self,
+        state: MutexGuard<'a, State>,
+        condition: impl FnMut(&mut State) -> bool,
+    ) -> MutexGuard<'a, State> {
+        self.changed
+            .wait_while(state, condition)
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl State {
+    fn is_running(&self, key: &str) -> bool {
+        self.running.values().any(|running| running.key == key)
+    }
+
+    fn is_full(&mut self) -> bool {
+        self.limit.is_some_and(|limit| self.running.len() >= limit)
+    }
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.processes.lock().running.remove(&self.id);
+        self.processes.changed.notify_all();
+    }
+}
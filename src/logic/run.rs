@@ -1,91 +1,526 @@
+use chrono::{DateTime, Local, TimeDelta};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
 use std::io::{stdout, Write};
-use std::path::PathBuf;
-use std::{fs, thread};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 
 use crate::error::{FinalResult, PrintingArgs, ResultPrinting};
-use crate::logic::functions::{execute, play_audio, send_notification};
-use crate::structs::config::{Config, Item};
+use crate::logic::audio::Audio;
+use crate::logic::control::{listen, Message, Request};
+use crate::logic::dispatch::dispatch;
+use crate::logic::functions::wait_until_or;
+use crate::logic::processes::Processes;
+use crate::structs::calendar::Calendar;
+use crate::structs::config::{diff_entries, Config, Entry, Item};
+use crate::structs::document::Settings;
+use crate::structs::include::load_sources;
+use crate::structs::misfire::{MisfireOverrides, MisfirePolicy, Missed};
+use crate::structs::once::once_path;
+use crate::structs::state::State;
 
-pub fn run(config_path: &PathBuf) -> FinalResult {
-    let mut config = Config::new(serde_yaml::from_str::<Vec<Item>>(&fs::read_to_string(
-        config_path,
-    )?)?);
+const MISFIRE_THRESHOLD_SECONDS: i64 = 2;
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
 
-    match fs::write(config_path, serde_yaml::to_string(&config.items)?) {
-        Ok(_) => {}
-        Err(e) => {
-            eprintln!("配置重新写入时遇到了错误: {}", e);
-        }
-    }
+pub fn run(
+    config_paths: &[PathBuf],
+    state_path: Option<PathBuf>,
+    overrides: MisfireOverrides,
+    socket: Option<&str>,
+) -> FinalResult {
+    let (mut entries, mut config, mut calendar) = load(config_paths, overrides)?;
+
+    let state_path = state_path.unwrap_or_else(|| config_paths[0].with_extension("state.yaml"));
+    let mut state = State::load(&state_path)?;
+    config.drop_done(&state.done);
 
     config.print();
     config.parse_notification();
 
+    if let Some(last_run) = state.last_run {
+        println!(
+            "上次运行到 {}，错过的任务将按照设置处理",
+            last_run.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    config.resume_from(state.last_run);
+
+    let processes = Arc::new(Processes::default());
+    let audio = Arc::new(Audio::new(config.audio_mode));
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = watch(&config, sender.clone());
+    if let Some(socket) = socket {
+        listen(socket, sender.clone())?;
+    }
+
     println!();
 
+    let mut paused = false;
+    let mut pending = None;
+    let mut announced = false;
+    let mut position = config.position();
+
     loop {
-        let (next, duration) = config.next();
-        println!("下一次执行时间：{}", next.time);
-        thread::sleep(duration);
-
-        for command in &next.commands {
-            if command.notify == -2 {
-                let command = command.clone();
-                let parameters;
-                println!(
-                    "为命令 {}{}发送通知",
-                    command.command,
-                    if command.parameters.is_empty() {
-                        " "
-                    } else {
-                        parameters = format!("（参数：{}）", command.parameters);
-                        &parameters
-                    }
-                );
+        let current = match pending.take() {
+            Some(current) => current,
+            None => {
+                announced = false;
+                catch_up(
+                    &mut config,
+                    &calendar,
+                    (&mut state, &state_path),
+                    paused,
+                    (&processes, &audio),
+                )?;
+                position = config.position();
+                config.next()
+            }
+        };
 
-                thread::spawn(move || {
-                    send_notification(&command.command)
-                        .result_println(PrintingArgs::customized("发送通知时遇到了问题"));
-                });
-            } else if command.audio {
-                let path = command.command.clone();
-                println!("播放音频 {}", path);
-
-                thread::spawn(move || {
-                    play_audio(PathBuf::from(&path))
-                        .result_println(PrintingArgs::customized("播放音频时遇到了问题"));
-                });
-            } else {
-                let command = command.clone();
-                let parameters_string;
-                println!(
-                    "执行命令 {}{}",
-                    command.command,
-                    if command.parameters.is_empty() {
-                        ""
-                    } else {
-                        parameters_string = format!("（参数：{}）", command.parameters);
-                        &parameters_string
-                    }
-                );
+        let message = match &current {
+            None => {
+                if watcher.is_none() && socket.is_none() {
+                    println!("没有需要执行的任务了");
+                    return Ok(());
+                }
+
+                if !announced {
+                    println!("没有需要执行的任务了，将在配置文件更新后继续运行");
+                    stdout().flush()?;
+                }
+                receiver.recv().ok()
+            }
+            Some((_, _, deadline)) => {
+                if !announced {
+                    println!("下一次执行时间：{}", deadline.format("%Y-%m-%d %H:%M:%S"));
+                    stdout().flush()?;
+                }
+                wait_until_or(*deadline, &receiver)
+            }
+        };
+        announced = true;
 
-                thread::spawn(move || {
-                    execute(
-                        &command.command,
-                        Some(
-                            command
-                                .parameters
-                                .split_whitespace()
-                                .map(|s| s.to_string())
-                                .collect(),
-                        ),
-                    )
-                    .result_println(PrintingArgs::customized("执行命令时遇到了问题"));
-                });
+        if let Some(message) = message {
+            match message {
+                Message::Reload => {
+                    reload(
+                        config_paths,
+                        overrides,
+                        &receiver,
+                        &state.done,
+                        (&mut entries, &mut config, &mut calendar),
+                    );
+                    watcher = watch(&config, sender.clone());
+                }
+                Message::Control(Request::Reload, reply) => {
+                    reload(
+                        config_paths,
+                        overrides,
+                        &receiver,
+                        &state.done,
+                        (&mut entries, &mut config, &mut calendar),
+                    );
+                    watcher = watch(&config, sender.clone());
+                    let _ = reply.send("已重新载入配置".to_string());
+                }
+                Message::Control(Request::Stop, reply) => {
+                    println!("收到停止请求，程序退出");
+                    let _ = reply.send("守护进程已停止".to_string());
+                    return Ok(());
+                }
+                Message::Control(request, reply) => {
+                    let _ = reply.send(respond(
+                        request,
+                        &config,
+                        current.as_ref(),
+                        &mut paused,
+                        (&processes, &audio),
+                    ));
+                    pending = Some(current);
+                }
             }
+            continue;
+        }
+
+        let Some((index, next, deadline)) = current else {
+            continue;
+        };
+
+        if Local::now() - deadline >= TimeDelta::seconds(MISFIRE_THRESHOLD_SECONDS) {
+            config.seek(position);
+            continue;
+        }
+
+        let skip_reason = paused
+            .then(|| "已暂停".to_string())
+            .or_else(|| calendar.skip_reason(next.date(deadline), &next));
+
+        state.last_run = Some(deadline);
+        if skip_reason.is_none() {
+            state.last_fired.insert(config.key(index), deadline);
+        }
+        if next.once.is_some() {
+            state.done.insert(config.key(index), deadline);
+        }
+        state
+            .save(&state_path)
+            .result_println(PrintingArgs::customized("保存状态时遇到了问题"));
+
+        if let Some(reason) = skip_reason {
+            println!("跳过 {} 的任务：{}", next.fire_time(), reason);
             println!();
+            stdout().flush()?;
+            continue;
         }
 
+        fire(&config, &next, deadline, (&processes, &audio));
+
         stdout().flush()?;
     }
 }
+
+fn catch_up(
+    config: &mut Config,
+    calendar: &Calendar,
+    (state, state_path): (&mut State, &Path),
+    paused: bool,
+    (processes, audio): (&Arc<Processes>, &Arc<Audio>),
+) -> FinalResult {
+    let until = Local::now() - TimeDelta::seconds(MISFIRE_THRESHOLD_SECONDS);
+    let missed = config.take_missed(until);
+    if missed.is_empty() {
+        return Ok(());
+    }
+
+    for missed in missed {
+        let item = config.item(missed.index, missed.last);
+        let key = config.key(missed.index);
+        let skip_reason = paused
+            .then(|| "已暂停".to_string())
+            .or_else(|| calendar.skip_reason(item.date(missed.last), &item))
+            .or_else(|| misfire_reason(config, &item, &missed));
+
+        if item.once.is_some() {
+            state.done.insert(key.clone(), missed.last);
+        }
+
+        match skip_reason {
+            Some(reason) => {
+                println!("跳过 {} 的任务：{}，{}", item.fire_time(), missed, reason);
+                println!();
+            }
+            None => {
+                state.last_fired.insert(key, missed.last);
+                println!("补执行 {} 的任务：{}，只执行一次", item.fire_time(), missed);
+                println!();
+                fire(config, &item, missed.last, (processes, audio));
+            }
+        }
+    }
+
+    state.last_run = Some(until);
+    state
+        .save(state_path)
+        .result_println(PrintingArgs::customized("保存状态时遇到了问题"));
+    stdout().flush()?;
+
+    Ok(())
+}
+
+fn fire(
+    config: &Config,
+    item: &Item,
+    time: DateTime<Local>,
+    (processes, audio): (&Arc<Processes>, &Arc<Audio>),
+) {
+    processes.set_limit(config.max_processes);
+    audio.set_mode(config.audio_mode);
+    dispatch(
+        item.commands
+            .iter()
+            .map(|command| command.render(time, item))
+            .collect(),
+        item.sequential,
+        config.log.clone(),
+        processes.clone(),
+        audio.clone(),
+    );
+}
+
+fn respond(
+    request: Request,
+    config: &Config,
+    current: Option<&(usize, Item, DateTime<Local>)>,
+    paused: &mut bool,
+    (processes, audio): (&Arc<Processes>, &Arc<Audio>),
+) -> String {
+    let now = Local::now();
+    let next_time = |index: usize| {
+        config
+            .occurrence(index, now, false)
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "不再执行".to_string())
+    };
+
+    match request {
+        Request::Status => format!(
+            "状态：{}\n配置文件：{}\n任务数量：{}\n下一次执行时间：{}\n正在运行的命令：{} 个",
+            if *paused { "已暂停" } else { "运行中" },
+            config
+                .sources
+                .iter()
+                .filter(|path| path.exists())
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join("、"),
+            config.task_count(),
+            current
+                .map(|(_, _, deadline)| deadline.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "无".to_string()),
+            processes.running()
+        ),
+        Request::Next => match current {
+            Some((index, item, deadline)) => {
+                let mut response = format!(
+                    "下一次执行时间：{}（任务 {}）",
+                    deadline.format("%Y-%m-%d %H:%M:%S"),
+                    index + 1
+                );
+                for command in &item.commands {
+                    response.push_str(&format!(
+                        "\n  {}{}{}",
+                        command.name(),
+                        command.arguments_note(),
+                        command.source_note()
+                    ));
+                }
+                response
+            }
+            None => "没有需要执行的任务了".to_string(),
+        },
+        Request::List => (0..config.task_count())
+            .map(|index| {
+                format!(
+                    "{}. {}，下一次执行时间：{}",
+                    index + 1,
+                    config.key(index),
+                    next_time(index)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Request::Pause if *paused => "已经处于暂停状态".to_string(),
+        Request::Pause => {
+            *paused = true;
+            println!("收到暂停请求，到达执行时间的任务将被跳过");
+            "已暂停，到达执行时间的任务将被跳过".to_string()
+        }
+        Request::Resume if !*paused => "没有处于暂停状态".to_string(),
+        Request::Resume => {
+            *paused = false;
+            println!("收到恢复请求，任务将按时执行");
+            "已恢复".to_string()
+        }
+        Request::Trigger(item) => match item
+            .parse::<usize>()
+            .ok()
+            .filter(|index| (1..=config.task_count()).contains(index))
+        {
+            Some(index) => {
+                println!("收到触发请求，立即执行任务 {}", config.key(index - 1));
+                println!();
+
+                fire(
+                    config,
+                    &config.item(index - 1, now),
+                    now,
+                    (processes, audio),
+                );
+                format!("已触发任务 {}", config.key(index - 1))
+            }
+            None => format!("没有找到任务 {}，请使用 list 命令查看任务编号", item),
+        },
+        Request::Silence => match audio.silence() {
+            0 => "没有正在播放的声音".to_string(),
+            count => {
+                println!("收到静音请求，停止了 {} 个声音", count);
+                println!();
+                format!("已停止 {} 个正在播放或等待播放的声音", count)
+            }
+        },
+        Request::Reload | Request::Stop => unreachable!(),
+    }
+}
+
+fn misfire_reason(config: &Config, item: &Item, missed: &Missed) -> Option<String> {
+    let misfire = item.misfire.unwrap_or(config.misfire);
+    let late = (Local::now() - missed.last).num_seconds();
+
+    match misfire.policy {
+        MisfirePolicy::Skip => Some("按照设置跳过错过的任务".to_string()),
+        MisfirePolicy::RunOnce => None,
+        MisfirePolicy::Grace => (late > misfire.grace as i64).then(|| {
+            format!(
+                "最近一次已错过 {} 秒，超过了允许的 {} 秒",
+                late, misfire.grace
+            )
+        }),
+    }
+}
+
+fn load(
+    config_paths: &[PathBuf],
+    overrides: MisfireOverrides,
+) -> FinalResult<(Vec<Entry>, Config, Calendar)> {
+    let once = once_path(&config_paths[0]);
+    let mut source_paths = config_paths.to_vec();
+    if once.exists() {
+        source_paths.push(once.clone());
+    }
+
+    let sources = load_sources(&source_paths)?;
+    let attribute = sources.iter().filter(|source| source.path != once).count() > 1;
+    let paths: Vec<PathBuf> = sources.iter().map(|source| source.path.clone()).collect();
+
+    let mut settings = Settings::default();
+    let mut entries = Vec::new();
+    for source in sources {
+        settings.merge(source.settings);
+        if attribute {
+            println!("已载入配置文件 {}", source.path.display());
+        }
+
+        entries.extend(source.entries.into_iter().map(|mut entry| {
+            if attribute {
+                entry.set_source(&source.path);
+            }
+            entry
+        }));
+    }
+
+    let mut config = Config::new(entries.clone())?;
+    config.misfire = overrides.apply(settings.misfire.unwrap_or_default());
+    config.log = Some(
+        settings
+            .log
+            .unwrap_or_else(|| config_paths[0].with_extension("log")),
+    );
+    config.max_processes = settings.max_processes;
+    config.audio_mode = settings.audio_mode.unwrap_or_default();
+    config.sources = paths;
+    config.sources.push(once);
+
+    let mut calendar = Calendar::default();
+    for path in &config.calendars {
+        let loaded = Calendar::load(path)?;
+        println!(
+            "已载入日历 {}，共 {} 个例外日期",
+            path.display(),
+            loaded.exceptions.len()
+        );
+        calendar.exceptions.extend(loaded.exceptions);
+    }
+    config.add_items(calendar.alternate_items());
+
+    if let Some(timezone) = settings.timezone {
+        config.set_default_timezone(timezone);
+    }
+
+    Ok((entries, config, calendar))
+}
+
+fn reload(
+    config_paths: &[PathBuf],
+    overrides: MisfireOverrides,
+    receiver: &Receiver<Message>,
+    done: &BTreeMap<String, DateTime<Local>>,
+    (entries, config, calendar): (&mut Vec<Entry>, &mut Config, &mut Calendar),
+) {
+    thread::sleep(RELOAD_DEBOUNCE);
+    while let Ok(message) = receiver.try_recv() {
+        if let Message::Control(_, reply) = message {
+            let _ = reply.send("正在重新载入配置，请稍后重试".to_string());
+        }
+    }
+
+    println!("检测到配置文件变化，重新载入配置");
+
+    match load(config_paths, overrides) {
+        Ok((new_entries, mut new_config, new_calendar)) => {
+            let changes = diff_entries(entries, &new_entries);
+            if changes.is_empty() {
+                println!("配置没有变化");
+            } else {
+                changes.iter().for_each(|change| println!("{}", change));
+            }
+
+            new_config.drop_done(done);
+            new_config.parse_notification();
+            *entries = new_entries;
+            *config = new_config;
+            *calendar = new_calendar;
+        }
+        Err(e) => {
+            e.result_println(
+                PrintingArgs::unexpected().err_message("新的配置有误，将继续使用原配置"),
+            );
+            config.reset_cursor();
+        }
+    }
+
+    println!();
+}
+
+fn watch(config: &Config, sender: Sender<Message>) -> Option<RecommendedWatcher> {
+    let paths: Vec<PathBuf> = config
+        .sources
+        .iter()
+        .chain(&config.calendars)
+        .cloned()
+        .collect();
+    let file_names: Vec<OsString> = paths
+        .iter()
+        .filter_map(|path| path.file_name().map(|name| name.to_os_string()))
+        .collect();
+
+    let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if let Ok(event) = event {
+            if !matches!(event.kind, EventKind::Access(_))
+                && event.paths.iter().any(|path| {
+                    path.file_name()
+                        .is_some_and(|name| file_names.iter().any(|file| file == name))
+                })
+            {
+                let _ = sender.send(Message::Reload);
+            }
+        }
+    });
+
+    let result = watcher.and_then(|mut watcher| {
+        for path in &paths {
+            let directory = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            watcher.watch(directory, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(watcher)
+    });
+
+    match result {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            eprintln!(
+                "监视配置文件时遇到了问题，配置文件更新后需要重新启动：{}",
+                e
+            );
+            None
+        }
+    }
+}
@@ -1,13 +1,21 @@
 use clap::Parser;
 
 use error::PrintingArgs;
+use logic::add_once::add_once;
+use logic::audio::{Audio, Sound};
+use logic::control::{control, Request};
 use logic::create_config::create_config;
-use logic::functions::{execute, play_audio, send_notification};
+use logic::format::format;
+use logic::functions::{execute, send_notification, timer};
 use logic::run::run;
 
 use crate::args::{Actions, Args, Functions};
+use crate::error::NormalError::Input;
 use crate::error::ResultPrinting;
 use crate::logic::functions::time;
+use crate::structs::audio_options::AudioOptions;
+use crate::structs::config::Command;
+use crate::structs::misfire::MisfireOverrides;
 
 mod args;
 mod error;
@@ -19,15 +27,106 @@ static APP_NAME: &str = "BoomYouUpR";
 
 fn main() {
     match Args::parse().action {
-        Actions::Run { config } => run(&config),
+        Actions::Run {
+            config,
+            state,
+            misfire,
+            grace,
+            daemon,
+            connection,
+        } => run(
+            &config,
+            state,
+            MisfireOverrides {
+                policy: misfire.map(Into::into),
+                grace,
+            },
+            daemon.then_some(connection.socket.as_str()),
+        ),
+        Actions::Format { config } => format(&config),
         Actions::Configure { config } => create_config(&config),
+        Actions::AddOnce {
+            config,
+            at,
+            command,
+            args,
+            shell,
+            audio,
+            notify,
+        } => add_once(
+            &config,
+            &at,
+            Command {
+                command,
+                shell,
+                args,
+                audio,
+                notify: notify.map_or(-1, |notify| notify as isize),
+                ..Default::default()
+            },
+        ),
+        Actions::Timer {
+            duration,
+            label,
+            audio,
+            notify,
+        } => {
+            timer(&duration, label.as_deref(), audio, notify)
+                .result_println(PrintingArgs::normal());
+            Ok(())
+        }
+        Actions::Status { connection } => control(&connection.socket, Request::Status),
+        Actions::Next { connection } => control(&connection.socket, Request::Next),
+        Actions::List { connection } => control(&connection.socket, Request::List),
+        Actions::Pause { connection } => control(&connection.socket, Request::Pause),
+        Actions::Resume { connection } => control(&connection.socket, Request::Resume),
+        Actions::Trigger { item, connection } => {
+            control(&connection.socket, Request::Trigger(item))
+        }
+        Actions::Reload { connection } => control(&connection.socket, Request::Reload),
+        Actions::Silence { connection } => control(&connection.socket, Request::Silence),
+        Actions::Stop { connection } => control(&connection.socket, Request::Stop),
         Actions::Test { function } => {
             match function {
                 Functions::Execute {
                     command,
                     parameters,
-                } => execute(&command, parameters),
-                Functions::PlayAudio { path } => play_audio(path),
+                    shell,
+                    wait,
+                } => execute(
+                    &Command {
+                        command,
+                        shell,
+                        args: parameters.unwrap_or_default(),
+                        wait,
+                        ..Default::default()
+                    },
+                    None,
+                    None,
+                ),
+                Functions::PlayAudio {
+                    path,
+                    volume,
+                    loops,
+                    fade_in,
+                    fade_out,
+                    max_duration,
+                } => {
+                    let options = AudioOptions {
+                        volume,
+                        loops,
+                        fade_in,
+                        fade_out,
+                        max_duration,
+                    };
+                    match options.check() {
+                        Ok(()) => Audio::default().play(Sound::File(path), options),
+                        Err(message) => {
+                            println!("{}", message);
+                            Err(Input)
+                        }
+                    }
+                }
                 Functions::SendNotification => send_notification("测试"),
                 Functions::Time {
                     hour,
@@ -0,0 +1,20 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioMode {
+    #[default]
+    Mix,
+    Queue,
+}
+
+impl Display for AudioMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioMode::Mix => write!(f, "同时播放"),
+            AudioMode::Queue => write!(f, "依次播放"),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AudioOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f32>,
+    #[serde(default, rename = "loop", skip_serializing_if = "Option::is_none")]
+    pub loops: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fade_in: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fade_out: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<u64>,
+}
+
+impl AudioOptions {
+    pub fn is_default(&self) -> bool {
+        *self == AudioOptions::default()
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        if self.loops == Some(0) && self.max_duration.is_none() {
+            return Err(
+                "loop 为 0 时需要同时指定 max_duration，否则音频将一直循环播放".to_string(),
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume.unwrap_or(1.0).max(0.0)
+    }
+
+    pub fn loops(&self) -> u32 {
+        self.loops.unwrap_or(1)
+    }
+
+    pub fn fade_in(&self) -> f64 {
+        self.fade_in.unwrap_or_default().max(0.0)
+    }
+
+    pub fn fade_out(&self) -> f64 {
+        self.fade_out.unwrap_or_default().max(0.0)
+    }
+
+    pub fn duration(&self, length: f64) -> Option<f64> {
+        let looped = match self.loops() {
+            0 => None,
+            loops => Some(length * loops as f64),
+        };
+
+        match (looped, self.max_duration) {
+            (Some(looped), Some(max)) => Some(looped.min(max as f64)),
+            (looped, max) => looped.or(max.map(|max| max as f64)),
+        }
+    }
+}
+
+impl Display for AudioOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+
+        if let Some(volume) = self.volume {
+            parts.push(format!("音量 {}%", (volume * 100.0).round()));
+        }
+        match self.loops {
+            Some(0) => parts.push("循环播放".to_string()),
+            Some(loops) if loops > 1 => parts.push(format!("播放 {} 遍", loops)),
+            _ => {}
+        }
+        if let Some(fade_in) = self.fade_in {
+            parts.push(format!("淡入 {} 秒", fade_in));
+        }
+        if let Some(fade_out) = self.fade_out {
+            parts.push(format!("淡出 {} 秒", fade_out));
+        }
+        if let Some(max_duration) = self.max_duration {
+            parts.push(format!("最长播放 {} 秒", max_duration));
+        }
+
+        write!(f, "{}", parts.join("，"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_combines_loops_and_max_duration() {
+        let options = |loops, max_duration| AudioOptions {
+            loops,
+            max_duration,
+            ..Default::default()
+        };
+
+        assert_eq!(options(None, None).duration(3.0), Some(3.0));
+        assert_eq!(options(Some(2), None).duration(3.0), Some(6.0));
+        assert_eq!(options(Some(2), Some(4)).duration(3.0), Some(4.0));
+        assert_eq!(options(Some(0), Some(10)).duration(3.0), Some(10.0));
+        assert_eq!(options(Some(0), None).duration(3.0), None);
+    }
+
+    #[test]
+    fn check_rejects_endless_loops() {
+        let endless = AudioOptions {
+            loops: Some(0),
+            ..Default::default()
+        };
+        assert!(endless.check().is_err());
+        assert!(AudioOptions {
+            max_duration: Some(60),
+            ..endless
+        }
+        .check()
+        .is_ok());
+    }
+}
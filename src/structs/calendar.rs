@@ -0,0 +1,377 @@
+use chrono::{Datelike, Days, Local, NaiveDate};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::error::FinalResult;
+use crate::structs::config::Item;
+use crate::structs::recurrence::{DateRange, Recurrence};
+
+const RECURRENCE_HORIZON_DAYS: u64 = 3660;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CalendarReference {
+    pub calendar: PathBuf,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    pub exceptions: Vec<Exception>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exception {
+    pub date: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<NaiveDate>,
+    pub action: Action,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "checked_items"
+    )]
+    pub items: Vec<Item>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    SkipAll,
+    SkipTagged,
+    Alternate,
+}
+
+impl Calendar {
+    pub fn load(path: &Path) -> FinalResult<Self> {
+        let content = fs::read_to_string(path)?;
+
+        let exceptions = if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("ics"))
+        {
+            parse_ics(&content)
+        } else {
+            serde_yaml::from_str(&content)?
+        };
+
+        Ok(Calendar { exceptions })
+    }
+
+    pub fn alternate_items(&self) -> Vec<Item> {
+        self.exceptions
+            .iter()
+            .filter(|exception| exception.action == Action::Alternate)
+            .flat_map(|exception| {
+                exception.items.iter().map(|item| Item {
+                    recurrence: Some(Recurrence {
+                        ranges: vec![DateRange {
+                            from: exception.date,
+                            to: exception.last_date(),
+                        }],
+                        ..item.recurrence.clone().unwrap_or_default()
+                    }),
+                    alternate: true,
+                    ..item.clone()
+                })
+            })
+            .collect()
+    }
+
+    pub fn skip_reason(&self, date: NaiveDate, item: &Item) -> Option<String> {
+        self.exceptions
+            .iter()
+            .filter(|exception| exception.contains(date))
+            .find(|exception| match exception.action {
+                Action::SkipAll => true,
+                Action::SkipTagged => item.tags.iter().any(|tag| exception.tags.contains(tag)),
+                Action::Alternate => !item.alternate,
+            })
+            .map(|exception| {
+                let action = match exception.action {
+                    Action::SkipAll => "跳过全部任务",
+                    Action::SkipTagged => "跳过带有指定标签的任务",
+                    Action::Alternate => "使用替代日程",
+                };
+
+                match &exception.reason {
+                    Some(reason) => format!("{}，{}", reason, action),
+                    None => action.to_string(),
+                }
+            })
+    }
+}
+
+impl Exception {
+    pub fn last_date(&self) -> NaiveDate {
+        self.to.unwrap_or(self.date)
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.date <= date && date <= self.last_date()
+    }
+}
+
+#[derive(Default)]
+struct IcsEvent {
+    date: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    reason: Option<String>,
+    tags: Vec<String>,
+    rule: Option<String>,
+}
+
+fn parse_ics(content: &str) -> Vec<Exception> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut exceptions = Vec::new();
+    let mut event: Option<IcsEvent> = None;
+
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.split(';').next().unwrap_or_default();
+
+        match (name, &mut event) {
+            ("BEGIN", _) if value == "VEVENT" => event = Some(IcsEvent::default()),
+            ("END", _) if value == "VEVENT" => {
+                if let Some(IcsEvent {
+                    date: Some(date),
+                    end,
+                    reason,
+                    tags,
+                    rule,
+                }) = event.take()
+                {
+                    let to = end.and_then(|end| end.pred_opt()).filter(|to| *to > date);
+                    let dates = match rule.as_deref().map(|rule| (rule, expand_rule(date, rule))) {
+                        None => vec![date],
+                        Some((_, Some(dates))) => dates,
+                        Some((rule, None)) => {
+                            println!(
+                                "日历事件 {} 的重复规则 {} 不受支持，只跳过 {} 这一次",
+                                reason.as_deref().unwrap_or_default(),
+                                rule,
+                                date
+                            );
+                            vec![date]
+                        }
+                    };
+
+                    exceptions.extend(dates.into_iter().map(|start| Exception {
+                        date: start,
+                        to: to.map(|to| start + (to - date)),
+                        action: if tags.is_empty() {
+                            Action::SkipAll
+                        } else {
+                            Action::SkipTagged
+                        },
+                        tags: tags.clone(),
+                        items: Vec::new(),
+                        reason: reason.clone(),
+                    }));
+                }
+            }
+            ("DTSTART", Some(event)) => event.date = parse_ics_date(value),
+            ("DTEND", Some(event)) => event.end = parse_ics_date(value),
+            ("RRULE", Some(event)) => event.rule = Some(value.to_string()),
+            ("SUMMARY", Some(event)) => event.reason = Some(split_ics_text(value).join(",")),
+            ("CATEGORIES", Some(event)) => event.tags.extend(
+                split_ics_text(value)
+                    .into_iter()
+                    .map(|tag| tag.trim().to_string()),
+            ),
+            _ => {}
+        }
+    }
+
+    exceptions
+}
+
+fn parse_ics_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+fn expand_rule(date: NaiveDate, rule: &str) -> Option<Vec<NaiveDate>> {
+    let mut frequency = None;
+    let mut interval = 1;
+    let mut count = None;
+    let mut until = None;
+
+    for part in rule.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key {
+            "FREQ" => frequency = Some(value),
+            "INTERVAL" => interval = value.parse::<u32>().ok().filter(|interval| *interval > 0)?,
+            "COUNT" => count = Some(value.parse::<usize>().ok()?),
+            "UNTIL" => until = Some(parse_ics_date(value)?),
+            "WKST" => {}
+            _ => return None,
+        }
+    }
+
+    let horizon = Local::now().date_naive() + Days::new(RECURRENCE_HORIZON_DAYS);
+    let until = until.map_or(horizon, |until| until.min(horizon));
+    let yearly = match frequency? {
+        "YEARLY" => true,
+        "DAILY" => false,
+        _ => return None,
+    };
+
+    let mut dates = Vec::new();
+    for step in 0.. {
+        if count.is_some_and(|count| dates.len() >= count) {
+            break;
+        }
+
+        let next = if yearly {
+            let year = date.year() + (step * interval) as i32;
+            if year > until.year() {
+                break;
+            }
+            match date.with_year(year) {
+                Some(next) => next,
+                None => continue,
+            }
+        } else {
+            date + Days::new(u64::from(step * interval))
+        };
+        if next > until {
+            break;
+        }
+
+        dates.push(next);
+    }
+
+    Some(dates)
+}
+
+fn split_ics_text(value: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = value.chars();
+
+    while let Some(char) = chars.next() {
+        let part = parts.last_mut().unwrap();
+        match (char, chars.clone().next()) {
+            ('\\', Some('n' | 'N')) => {
+                part.push('\n');
+                chars.next();
+            }
+            ('\\', Some(escaped @ ('\\' | ',' | ';'))) => {
+                part.push(escaped);
+                chars.next();
+            }
+            (',', _) => parts.push(String::new()),
+            _ => part.push(char),
+        }
+    }
+
+    parts
+}
+
+fn checked_items<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Item>, D::Error> {
+    let items = Vec::<Item>::deserialize(deserializer)?;
+    for item in &items {
+        item.check().map_err(de::Error::custom)?;
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ics_reads_events() {
+        let exceptions = parse_ics(
+            "BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20261001\r
+DTEND;VALUE=DATE:20261008\r
+SUMMARY:National\r
+  Day\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART:20261024T090000Z\r
+DTEND:20261025\r
+SUMMARY:Workday\r
+CATEGORIES:work, school\r
+END:VEVENT\r
+END:VCALENDAR\r
+",
+        );
+
+        assert_eq!(exceptions.len(), 2);
+
+        let holiday = &exceptions[0];
+        assert_eq!(holiday.date, NaiveDate::from_ymd_opt(2026, 10, 1).unwrap());
+        assert_eq!(holiday.to, NaiveDate::from_ymd_opt(2026, 10, 7));
+        assert_eq!(holiday.action, Action::SkipAll);
+        assert_eq!(holiday.reason.as_deref(), Some("National Day"));
+
+        let workday = &exceptions[1];
+        assert_eq!(workday.date, NaiveDate::from_ymd_opt(2026, 10, 24).unwrap());
+        assert_eq!(workday.to, None);
+        assert_eq!(workday.action, Action::SkipTagged);
+        assert_eq!(workday.tags, ["work", "school"]);
+    }
+
+    #[test]
+    fn parse_ics_expands_recurring_events() {
+        let exceptions = parse_ics(
+            "BEGIN:VEVENT
+DTSTART;VALUE=DATE:20240229
+RRULE:FREQ=YEARLY;COUNT=2
+SUMMARY:Leap day
+END:VEVENT
+BEGIN:VEVENT
+DTSTART;VALUE=DATE:20261001
+DTEND;VALUE=DATE:20261003
+RRULE:FREQ=DAILY;INTERVAL=7;UNTIL=20261015T000000Z
+SUMMARY:Break\\, part\\nweekly
+CATEGORIES:a\\,b,c
+END:VEVENT
+BEGIN:VEVENT
+DTSTART;VALUE=DATE:20261005
+RRULE:FREQ=WEEKLY;BYDAY=MO
+END:VEVENT
+",
+        );
+
+        let dates: Vec<_> = exceptions
+            .iter()
+            .map(|exception| {
+                (
+                    exception.date.to_string(),
+                    exception.to.map(|to| to.to_string()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                ("2024-02-29".to_string(), None),
+                ("2028-02-29".to_string(), None),
+                ("2026-10-01".to_string(), Some("2026-10-02".to_string())),
+                ("2026-10-08".to_string(), Some("2026-10-09".to_string())),
+                ("2026-10-15".to_string(), Some("2026-10-16".to_string())),
+                ("2026-10-05".to_string(), None),
+            ]
+        );
+        assert_eq!(exceptions[2].reason.as_deref(), Some("Break, part\nweekly"));
+        assert_eq!(exceptions[2].tags, ["a,b", "c"]);
+    }
+}
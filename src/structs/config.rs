@@ -1,35 +1,321 @@
-use chrono::{DateTime, Local, TimeZone, Timelike};
+use chrono::{
+    DateTime, Datelike, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeDelta,
+    TimeZone, Timelike,
+};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::ops::{Add, RangeBounds, Sub};
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+
+use chrono_tz::Tz;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_yaml::Value;
+
+use crate::error::NormalError::EmptyPlaylist;
+use crate::error::{FinalResult, NormalResult};
+use crate::structs::audio_mode::AudioMode;
+use crate::structs::audio_options::AudioOptions;
+use crate::structs::calendar::CalendarReference;
+use crate::structs::cron_item::{CronItem, CronTask};
+use crate::structs::include::IncludeReference;
+use crate::structs::misfire::{Misfire, Missed};
+use crate::structs::once::OnceItem;
+use crate::structs::overlap::Overlap;
+use crate::structs::playlist::{self, PlaylistOrder};
+use crate::structs::recurrence::{weekday_name, Recurrence};
+use crate::structs::repeat::Repeat;
+use crate::structs::retry::Retry;
+use crate::structs::tone::Tone;
+use crate::utils::arguments::{join_arguments, quote_argument, split_arguments};
+
+const MAX_LOOKAHEAD_DAYS: usize = 3660;
+const DST_MARGIN_SECONDS: u32 = 2 * 3600;
+const MAX_MISSED_COUNT: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Entry {
+    Time(Item),
+    Cron(CronItem),
+    Calendar(CalendarReference),
+    Include(IncludeReference),
+    Once(OnceItem),
+}
+
+impl<'de> Deserialize<'de> for Entry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let has = |key: &str| value.get(key).is_some();
+
+        if has("cron") {
+            serde_yaml::from_value(value).map(Entry::Cron)
+        } else if has("calendar") {
+            serde_yaml::from_value(value).map(Entry::Calendar)
+        } else if has("include") {
+            serde_yaml::from_value(value).map(Entry::Include)
+        } else if has("at") {
+            serde_yaml::from_value(value).map(Entry::Once)
+        } else {
+            serde_yaml::from_value(value).and_then(|item: Item| {
+                item.check()
+                    .map_err(<serde_yaml::Error as de::Error>::custom)?;
+                Ok(Entry::Time(item))
+            })
+        }
+        .map_err(de::Error::custom)
+    }
+}
+
+impl Entry {
+    pub fn label(&self) -> String {
+        match self {
+            Entry::Time(item) => item.time.to_string(),
+            Entry::Cron(item) => format!("Cron {}", item.cron),
+            Entry::Calendar(reference) => format!("日历 {}", reference.calendar.display()),
+            Entry::Include(reference) => format!("包含 {}", reference.include.join("、")),
+            Entry::Once(item) => format!("一次性 {}", item.at.format("%Y-%m-%d %H:%M:%S")),
+        }
+    }
+
+    pub fn set_source(&mut self, source: &Path) {
+        let commands = match self {
+            Entry::Time(item) => &mut item.commands,
+            Entry::Cron(item) => &mut item.commands,
+            Entry::Once(item) => &mut item.commands,
+            _ => return,
+        };
+
+        for command in commands {
+            command.source = Some(source.to_path_buf());
+        }
+    }
+}
+
+pub fn diff_entries(old: &[Entry], new: &[Entry]) -> Vec<String> {
+    let group = |entries: &[Entry]| {
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for entry in entries {
+            groups
+                .entry(entry.label())
+                .or_default()
+                .push(serde_yaml::to_string(entry).unwrap_or_default());
+        }
+        groups
+    };
+    let old = group(old);
+    let new = group(new);
+
+    let mut changes = Vec::new();
+    for (label, old_entries) in &old {
+        match new.get(label) {
+            None => changes.push(format!("- 移除了 {} 的任务", label)),
+            Some(new_entries) if new_entries != old_entries => {
+                changes.push(format!("* 修改了 {} 的任务", label))
+            }
+            _ => {}
+        }
+    }
+    for label in new.keys().filter(|label| !old.contains_key(*label)) {
+        changes.push(format!("+ 新增了 {} 的任务", label));
+    }
 
-use serde::{Deserialize, Serialize};
+    changes
+}
 
 #[derive(Debug)]
 pub struct Config {
     pub items: Vec<Item>,
-    next_index: usize,
+    pub cron_tasks: Vec<CronTask>,
+    pub calendars: Vec<PathBuf>,
+    pub sources: Vec<PathBuf>,
+    pub log: Option<PathBuf>,
+    pub max_processes: Option<usize>,
+    pub audio_mode: AudioMode,
+    pub misfire: Misfire,
+    cursor: DateTime<Local>,
+    last_index: Option<usize>,
 }
 
 impl Config {
-    pub fn new(mut items: Vec<Item>) -> Self {
-        items.sort_unstable_by(|a, b| a.time.cmp(&b.time));
+    pub fn new(entries: Vec<Entry>) -> FinalResult<Self> {
+        let mut items = Vec::new();
+        let mut cron_tasks = Vec::new();
+        let mut calendars = Vec::new();
+
+        for entry in entries {
+            match entry {
+                Entry::Time(mut item) => {
+                    for command in std::mem::take(&mut item.commands) {
+                        items.add_command(item.clone(), command);
+                    }
+                }
+                Entry::Once(item) => {
+                    let mut item = Item::from(item);
+                    for command in std::mem::take(&mut item.commands) {
+                        items.add_command(item.clone(), command);
+                    }
+                }
+                Entry::Cron(item) => cron_tasks.push(CronTask::new(item)?),
+                Entry::Calendar(reference) => calendars.push(reference.calendar),
+                Entry::Include(_) => {}
+            }
+        }
 
-        Self {
+        Ok(Self {
             items,
-            next_index: 0,
+            cron_tasks,
+            calendars,
+            sources: Vec::new(),
+            log: None,
+            max_processes: None,
+            audio_mode: AudioMode::default(),
+            misfire: Misfire::default(),
+            cursor: Local::now(),
+            last_index: None,
+        })
+    }
+
+    pub fn add_items(&mut self, items: Vec<Item>) {
+        self.items.extend(items);
+        self.items.sort_by_key(Item::fire_time);
+        self.reset_cursor();
+    }
+
+    pub fn next(&mut self) -> Option<(usize, Item, DateTime<Local>)> {
+        let (index, deadline) = (0..self.task_count())
+            .filter_map(|index| {
+                let inclusive = self.last_index.is_some_and(|last| index > last);
+
+                self.occurrence(index, self.cursor, inclusive)
+                    .map(|deadline| (index, deadline))
+            })
+            .min_by_key(|&(index, deadline)| (deadline, index))?;
+
+        self.cursor = deadline;
+        self.last_index = Some(index);
+
+        Some((index, self.item(index, deadline), deadline))
+    }
+
+    pub fn take_missed(&mut self, until: DateTime<Local>) -> Vec<Missed> {
+        let missed: Vec<Missed> = (0..self.task_count())
+            .filter_map(|index| {
+                let mut after = self.cursor;
+                let mut inclusive = self.last_index.is_some_and(|last| index > last);
+                let mut count = 0;
+                let mut exact = true;
+                let mut last = None;
+
+                while let Some(time) = self
+                    .occurrence(index, after, inclusive)
+                    .filter(|time| *time <= until)
+                {
+                    count += usize::from(exact);
+                    last = Some(time);
+                    (after, inclusive) = (time, false);
+
+                    if exact && count == MAX_MISSED_COUNT {
+                        exact = false;
+                        after = after.max(until - TimeDelta::days(1));
+                    }
+                }
+
+                last.map(|last| Missed {
+                    index,
+                    count,
+                    exact,
+                    last,
+                })
+            })
+            .collect();
+
+        if !missed.is_empty() {
+            self.cursor = until;
+            self.last_index = None;
         }
-        .updated_next_index()
+
+        missed
     }
 
-    pub fn next(&mut self) -> (&Item, Duration) {
-        let item = &self.items[self.next_index];
-        let duration = item.time.duration_from(Local::now());
+    pub fn position(&self) -> (DateTime<Local>, Option<usize>) {
+        (self.cursor, self.last_index)
+    }
 
-        self.next_index = (self.next_index + 1) % self.items.len();
+    pub fn seek(&mut self, (cursor, last_index): (DateTime<Local>, Option<usize>)) {
+        self.cursor = cursor;
+        self.last_index = last_index;
+    }
 
-        (item, duration)
+    pub fn item(&self, index: usize, deadline: DateTime<Local>) -> Item {
+        match self.items.get(index) {
+            Some(item) => item.clone(),
+            None => {
+                let task = &self.cron_tasks[index - self.items.len()];
+
+                Item {
+                    name: task.item.name.clone(),
+                    time: match task.item.timezone {
+                        Some(timezone) => {
+                            Time::from((deadline + task.advance).with_timezone(&timezone))
+                        }
+                        None => Time::from(deadline + task.advance),
+                    },
+                    timezone: task.item.timezone,
+                    tags: task.item.tags.clone(),
+                    misfire: task.item.misfire,
+                    sequential: task.item.sequential,
+                    commands: task.item.commands.clone(),
+                    advance: task.advance,
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    pub fn task_count(&self) -> usize {
+        self.items.len() + self.cron_tasks.len()
+    }
+
+    pub fn occurrence(
+        &self,
+        index: usize,
+        after: DateTime<Local>,
+        inclusive: bool,
+    ) -> Option<DateTime<Local>> {
+        match self.items.get(index) {
+            Some(item) => item.next_after(after, inclusive),
+            None => self.cron_tasks[index - self.items.len()].next_after(after, inclusive),
+        }
+    }
+
+    pub fn key(&self, index: usize) -> String {
+        match self.items.get(index) {
+            Some(item) => item_key(item),
+            None => {
+                let task = &self.cron_tasks[index - self.items.len()];
+                format!(
+                    "{} -{}s {}",
+                    task.item.cron,
+                    task.advance.num_seconds(),
+                    command_names(&task.item.commands)
+                )
+            }
+        }
+    }
+
+    pub fn drop_done(&mut self, done: &BTreeMap<String, DateTime<Local>>) {
+        self.items
+            .retain(|item| item.once.is_none() || !done.contains_key(&item_key(item)));
+        self.reset_cursor();
+    }
+
+    pub fn resume_from(&mut self, last_run: Option<DateTime<Local>>) {
+        self.reset_cursor();
+
+        if let Some(last_run) = last_run.filter(|last_run| *last_run < self.cursor) {
+            self.cursor = last_run;
+        }
     }
 
     pub fn parse_notification(&mut self) {
@@ -42,99 +328,523 @@ impl Config {
             for j in 0..item.commands.len() {
                 if item.commands[j].notify >= 0 {
                     result.add_command_reverse(
-                        item.time
-                            - Time::second(usize::try_from(item.commands[j].notify).unwrap_or(0)),
-                        Command {
-                            command: item.commands[j].command.clone(),
-                            parameters: item.commands[j].parameters.clone(),
-                            notify: -2,
-                            ..item.commands[j]
+                        Item {
+                            advance: TimeDelta::seconds(item.commands[j].notify as i64),
+                            sequential: false,
+                            once: None,
+                            commands: Vec::new(),
+                            ..item.clone()
                         },
+                        item.commands[j].notification(),
                     );
                 }
             }
         }
 
+        let mut cron_tasks = Vec::new();
+
+        for task in &self.cron_tasks {
+            cron_tasks.push(task.clone());
+
+            for command in &task.item.commands {
+                if command.notify >= 0 {
+                    cron_tasks
+                        .push(task.advanced(command.notify as usize, vec![command.notification()]));
+                }
+            }
+        }
+
         self.items = result;
-        self.update_next_index();
+        self.cron_tasks = cron_tasks;
+        self.reset_cursor();
     }
 
     pub fn print(&self) {
-        let items = &self.items;
         println!("配置解析中，配置如下：");
 
-        for item in items {
+        for item in &self.items {
+            let item = &item.shifted();
             print!("{} ", item.time);
 
-            let mut width = 0;
-            for command in &item.commands {
-                if width != 0 {
-                    println!();
-                }
+            if let Some(timezone) = &item.timezone {
+                println!(
+                    "时区：{}{}",
+                    timezone,
+                    local_equivalent(item.next_after(Local::now(), false))
+                );
+                print!("{:>9}", "");
+            }
 
-                println!("{:>width$}命令：{}", "", command.command, width = width);
+            if let Some(repeat) = &item.repeat {
+                println!("{}", repeat);
+                print!("{:>9}", "");
+            }
 
-                if width == 0 {
-                    width = 9;
+            match (&item.once, &item.recurrence) {
+                (Some(at), _) => {
+                    println!("仅在 {} 执行一次", at.format("%Y-%m-%d %H:%M:%S"));
+                    print!("{:>9}", "");
+                }
+                (None, Some(recurrence)) => {
+                    println!("重复：{}", recurrence);
+                    print!("{:>9}", "");
                 }
+                (None, None) => {}
+            }
 
-                println!(
-                    "{:>width$}参数：{}",
-                    "",
-                    if command.parameters.is_empty() {
-                        "无"
-                    } else {
-                        &command.parameters
-                    },
-                    width = width
-                );
+            if let Some(name) = &item.name {
+                println!("名称：{}", name);
+                print!("{:>9}", "");
+            }
+
+            if !item.tags.is_empty() {
+                println!("标签：{}", item.tags.join("、"));
+                print!("{:>9}", "");
+            }
+
+            if let Some(misfire) = &item.misfire {
+                println!("错过时：{}", misfire);
+                print!("{:>9}", "");
+            }
 
+            if item.sequential {
+                println!("顺序执行：是");
+                print!("{:>9}", "");
+            }
+
+            print_commands(&item.commands, Some(item.time));
+        }
+
+        for task in &self.cron_tasks {
+            println!("Cron 表达式：{}", task.item.cron);
+            println!(
+                "{:>9}接下来的执行时间：{}",
+                "",
+                task.upcoming(3)
+                    .iter()
+                    .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .collect::<Vec<_>>()
+                    .join("、")
+            );
+            print!("{:>9}", "");
+
+            if let Some(timezone) = &task.item.timezone {
                 println!(
-                    "{:>width$}音频：{}",
-                    "",
-                    if command.audio { "是" } else { "否" },
-                    width = width
+                    "时区：{}{}",
+                    timezone,
+                    local_equivalent(task.upcoming(1).first().copied())
                 );
+                print!("{:>9}", "");
+            }
+
+            if let Some(name) = &task.item.name {
+                println!("名称：{}", name);
+                print!("{:>9}", "");
+            }
+
+            if !task.item.tags.is_empty() {
+                println!("标签：{}", task.item.tags.join("、"));
+                print!("{:>9}", "");
+            }
+
+            if let Some(misfire) = &task.item.misfire {
+                println!("错过时：{}", misfire);
+                print!("{:>9}", "");
+            }
+
+            if task.item.sequential {
+                println!("顺序执行：是");
+                print!("{:>9}", "");
+            }
+
+            print_commands(&task.item.commands, None);
+        }
+    }
+
+    pub fn set_default_timezone(&mut self, timezone: Tz) {
+        for item in &mut self.items {
+            item.timezone.get_or_insert(timezone);
+        }
+
+        for task in &mut self.cron_tasks {
+            task.item.timezone.get_or_insert(timezone);
+        }
+    }
+
+    pub fn reset_cursor(&mut self) {
+        self.cursor = Local::now();
+        self.last_index = None;
+    }
+}
+
+pub fn resolve<Z: TimeZone>(timezone: &Z, time: NaiveDateTime) -> DateTime<Z> {
+    match timezone.from_local_datetime(&time) {
+        LocalResult::Single(time) => time,
+        LocalResult::Ambiguous(earliest, _) => earliest,
+        LocalResult::None => (1..=24 * 4)
+            .map(|quarters| {
+                timezone.from_local_datetime(&(time + TimeDelta::minutes(15 * quarters)))
+            })
+            .find_map(|time| time.earliest())
+            .unwrap_or_else(|| timezone.from_utc_datetime(&time)),
+    }
+}
+
+fn local_equivalent(time: Option<DateTime<Local>>) -> String {
+    match time {
+        Some(time) => format!(
+            "（下一次执行的本地时间为 {}）",
+            time.format("%Y-%m-%d %H:%M:%S")
+        ),
+        None => String::new(),
+    }
+}
+
+fn item_key(item: &Item) -> String {
+    match item.once {
+        Some(at) => format!(
+            "{} {}",
+            at.format("%Y-%m-%d %H:%M:%S"),
+            command_names(&item.commands)
+        ),
+        None => format!("{} {}", item.fire_time(), command_names(&item.commands)),
+    }
+}
 
-                let notify_str;
+fn command_names(commands: &[Command]) -> String {
+    commands
+        .iter()
+        .map(|command| command.name())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn print_commands(commands: &[Command], time: Option<Time>) {
+    let mut width = 0;
+    for command in commands {
+        if width != 0 {
+            println!();
+        }
+
+        match (&command.speak, &command.tone, &command.shell) {
+            (Some(text), _, _) => {
+                println!("{:>width$}朗读：{}", "", text, width = width);
+                if let Some(voice) = &command.voice {
+                    println!("{:>width$}语音：{}", "", voice, width = width);
+                }
+            }
+            (None, Some(tone), _) => {
+                println!("{:>width$}提示音：{}", "", tone, width = width)
+            }
+            (None, None, Some(shell)) => {
+                println!("{:>width$}脚本：{}", "", command.command, width = width);
+                println!("{:>width$}Shell：{}", "", shell, width = width);
+            }
+            (None, None, None) => {
+                println!("{:>width$}命令：{}", "", command.command, width = width)
+            }
+        }
+
+        if width == 0 {
+            width = 9;
+        }
+
+        println!(
+            "{:>width$}参数：{}",
+            "",
+            match command.arguments() {
+                arguments if arguments.is_empty() => "无".to_string(),
+                arguments => join_arguments(&arguments),
+            },
+            width = width
+        );
+
+        if !command.env.is_empty() {
+            println!(
+                "{:>width$}环境变量：{}",
+                "",
+                command
+                    .env
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, quote_argument(value)))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                width = width
+            );
+        }
+
+        if let Some(working_dir) = &command.working_dir {
+            println!(
+                "{:>width$}工作目录：{}",
+                "",
+                working_dir.display(),
+                width = width
+            );
+        }
+
+        if command.wait {
+            let timeout_str;
+            println!(
+                "{:>width$}等待结束并记录输出：是{}",
+                "",
+                match command.timeout {
+                    Some(timeout) => {
+                        timeout_str = format!("，超过 {} 秒时终止", timeout);
+                        &timeout_str
+                    }
+                    None => "",
+                },
+                width = width
+            );
+        }
+
+        println!(
+            "{:>width$}音频：{}",
+            "",
+            match (command.audio, command.audio_options.is_default()) {
+                (false, _) => "否".to_string(),
+                (true, true) => "是".to_string(),
+                (true, false) => format!("是（{}）", command.audio_options),
+            },
+            width = width
+        );
+
+        if command.audio && !command.is_single_track() {
+            match command.playlist() {
+                Ok(tracks) => {
+                    println!(
+                        "{:>width$}播放列表（{}）：",
+                        "",
+                        command.order,
+                        width = width
+                    );
+                    for (index, track) in tracks.iter().enumerate() {
+                        println!(
+                            "{:>width$}{}. {}",
+                            "",
+                            index + 1,
+                            track.display(),
+                            width = width + 2
+                        );
+                    }
+                }
+                Err(e) => println!("{:>width$}播放列表：{}", "", e, width = width),
+            }
+        }
+
+        let notify_str;
+        println!(
+            "{:>width$}发送通知：{}",
+            "",
+            match command.notify {
+                -2 => "是",
+                ..=-1 => "否",
+                0 => "开始运行时",
+                _ => {
+                    notify_str = match time {
+                        Some(time) => format!(
+                            "开始运行的 {} 秒之前，即 {}",
+                            command.notify,
+                            time - Time::second(command.notify as usize)
+                        ),
+                        None => format!("开始运行的 {} 秒之前", command.notify),
+                    };
+                    &notify_str
+                }
+            },
+            width = width
+        );
+
+        if let Some(retry) = &command.retry {
+            println!("{:>width$}失败时：{}", "", retry, width = width);
+        }
+
+        if !command.overlap.is_allow() {
+            println!(
+                "{:>width$}上一次运行未结束时：{}",
+                "",
+                command.overlap,
+                width = width
+            );
+        }
+
+        for (name, follow_ups) in [
+            ("成功后执行", &command.on_success),
+            ("失败后执行", &command.on_failure),
+        ] {
+            if !follow_ups.is_empty() {
                 println!(
-                    "{:>width$}发送通知：{}",
+                    "{:>width$}{}：{}",
                     "",
-                    match command.notify {
-                        -2 => "是",
-                        ..=-1 => "否",
-                        0 => "开始运行时",
-                        _ => {
-                            let time = item.time - Time::second(command.notify as usize);
-                            notify_str =
-                                format!("开始运行的 {} 秒之前，即 {}", command.notify, time);
-                            &notify_str
-                        }
-                    },
+                    name,
+                    follow_ups
+                        .iter()
+                        .map(|follow_up| follow_up.command.as_str())
+                        .collect::<Vec<_>>()
+                        .join("、"),
                     width = width
                 );
             }
         }
-    }
-
-    pub fn update_next_index(&mut self) {
-        self.next_index = self
-            .items
-            .iter()
-            .position(|item| item.time > Time::from(Local::now()))
-            .unwrap_or(0);
-    }
 
-    pub fn updated_next_index(mut self) -> Self {
-        self.update_next_index();
-        self
+        if let Some(source) = &command.source {
+            println!("{:>width$}来源：{}", "", source.display(), width = width);
+        }
     }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Item {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     pub time: Time,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<Repeat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub misfire: Option<Misfire>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sequential: bool,
     pub commands: Vec<Command>,
+    #[serde(skip)]
+    pub alternate: bool,
+    #[serde(skip)]
+    pub once: Option<NaiveDateTime>,
+    #[serde(skip)]
+    pub advance: TimeDelta,
+}
+
+impl Item {
+    pub fn next_after(&self, after: DateTime<Local>, inclusive: bool) -> Option<DateTime<Local>> {
+        let after = after + self.advance;
+        let time = match &self.timezone {
+            Some(timezone) => self.next_in(timezone, after, inclusive),
+            None => self.next_in(&Local, after, inclusive),
+        };
+
+        time.map(|time| time - self.advance)
+    }
+
+    pub fn date(&self, deadline: DateTime<Local>) -> NaiveDate {
+        let time = deadline + self.advance;
+        match &self.timezone {
+            Some(timezone) => time.with_timezone(timezone).date_naive(),
+            None => time.date_naive(),
+        }
+    }
+
+    pub fn fire_time(&self) -> Time {
+        self.time - Time::second(self.advance.num_seconds() as usize)
+    }
+
+    pub fn shifted(&self) -> Item {
+        let seconds = self.advance.num_seconds() as usize;
+        let days_before = self.notify_days_before(seconds as isize) as i64;
+
+        Item {
+            time: self.fire_time(),
+            repeat: self.repeat.map(|repeat| repeat.shifted(seconds)),
+            recurrence: self
+                .recurrence
+                .as_ref()
+                .map(|recurrence| recurrence.shifted(-days_before)),
+            advance: TimeDelta::zero(),
+            ..self.clone()
+        }
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        match &self.repeat {
+            Some(repeat) if repeat.until < self.time => Err(format!(
+                "repeat 中的 until {} 早于开始时间 {}，重复执行的时间段不能跨越午夜",
+                repeat.until, self.time
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn next_in<Z: TimeZone>(
+        &self,
+        timezone: &Z,
+        after: DateTime<Local>,
+        inclusive: bool,
+    ) -> Option<DateTime<Local>> {
+        let zoned = after.with_timezone(timezone);
+        let first_date = zoned.date_naive();
+        let earlier = after - TimeDelta::seconds(DST_MARGIN_SECONDS as i64);
+        let from = if earlier.with_timezone(timezone).offset().fix() == zoned.offset().fix() {
+            zoned.num_seconds_from_midnight()
+        } else {
+            zoned
+                .num_seconds_from_midnight()
+                .saturating_sub(DST_MARGIN_SECONDS)
+        };
+
+        first_date
+            .iter_days()
+            .take(MAX_LOOKAHEAD_DAYS)
+            .filter(|date| self.runs_on(*date))
+            .flat_map(move |date| {
+                self.times(if date == first_date { from } else { 0 })
+                    .map(move |time| resolve(timezone, date.and_time(time)).with_timezone(&Local))
+            })
+            .find(|time| *time > after || (inclusive && *time == after))
+    }
+
+    fn times(&self, from: u32) -> impl Iterator<Item = NaiveTime> {
+        let start = self.time.seconds_from_midnight();
+        let (until, every) = match &self.repeat {
+            Some(repeat) => (repeat.until.seconds_from_midnight(), repeat.every),
+            None => (start, 1),
+        };
+        let first = match from.checked_sub(start) {
+            Some(elapsed) => start + elapsed.div_ceil(every) * every,
+            None => start,
+        };
+
+        (first..=until)
+            .step_by(every as usize)
+            .filter_map(|seconds| NaiveTime::from_num_seconds_from_midnight_opt(seconds, 0))
+    }
+
+    pub fn same_schedule(&self, other: &Item) -> bool {
+        self.name == other.name
+            && self.time == other.time
+            && self.timezone == other.timezone
+            && self.repeat == other.repeat
+            && self.recurrence == other.recurrence
+            && self.tags == other.tags
+            && self.misfire == other.misfire
+            && self.sequential == other.sequential
+            && self.alternate == other.alternate
+            && self.once == other.once
+            && self.advance == other.advance
+    }
+
+    pub fn runs_on(&self, date: NaiveDate) -> bool {
+        self.recurrence
+            .as_ref()
+            .is_none_or(|recurrence| recurrence.runs_on(date))
+    }
+
+    pub fn notify_days_before(&self, notify: isize) -> usize {
+        let notify = usize::try_from(notify).unwrap_or(0);
+        let seconds = self.time.hour as usize * 3600
+            + self.time.minute as usize * 60
+            + self.time.second as usize;
+
+        if notify > seconds {
+            (notify - seconds - 1) / 86400 + 1
+        } else {
+            0
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
@@ -166,6 +876,23 @@ impl<T: TimeZone> From<DateTime<T>> for Time {
     }
 }
 
+impl From<NaiveTime> for Time {
+    fn from(time: NaiveTime) -> Self {
+        Time {
+            hour: time.hour() as u8,
+            minute: time.minute() as u8,
+            second: time.second() as u8,
+        }
+    }
+}
+
+impl From<Time> for NaiveTime {
+    fn from(time: Time) -> Self {
+        NaiveTime::from_hms_opt(time.hour as u32, time.minute as u32, time.second as u32)
+            .unwrap_or_default()
+    }
+}
+
 impl Ord for Time {
     fn cmp(&self, other: &Self) -> Ordering {
         if self.hour != other.hour {
@@ -239,6 +966,10 @@ impl Sub for Time {
 }
 
 impl Time {
+    pub fn seconds_from_midnight(&self) -> u32 {
+        self.hour as u32 * 3600 + self.minute as u32 * 60 + self.second as u32
+    }
+
     pub fn second(second: usize) -> Self {
         let mut second = second;
         let mut minute = 0;
@@ -264,63 +995,287 @@ impl Time {
             second: second as u8,
         }
     }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawCommand")]
+pub struct Command {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speak: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tone: Option<Tone>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub parameters: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub wait: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<Retry>,
+    #[serde(skip_serializing_if = "Overlap::is_allow")]
+    pub overlap: Overlap,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub on_success: Vec<Command>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub on_failure: Vec<Command>,
+    pub audio: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tracks: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "PlaylistOrder::is_in_order")]
+    pub order: PlaylistOrder,
+    #[serde(flatten)]
+    pub audio_options: AudioOptions,
+    pub notify: isize,
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+}
 
-    pub fn duration_from<T: TimeZone>(&self, time: DateTime<T>) -> Duration {
-        let from_milliseconds = time.nanosecond() / 1_000_000;
-        let from_seconds = time.second();
-        let from_minutes = time.minute();
-        let from_hours = time.hour();
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCommand {
+    #[serde(default)]
+    command: String,
+    #[serde(default)]
+    speak: Option<String>,
+    #[serde(default)]
+    voice: Option<String>,
+    #[serde(default)]
+    tone: Option<Tone>,
+    #[serde(default)]
+    shell: Option<String>,
+    #[serde(default)]
+    parameters: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    working_dir: Option<PathBuf>,
+    #[serde(default)]
+    wait: bool,
+    #[serde(default)]
+    timeout: Option<u64>,
+    #[serde(default)]
+    retry: Option<Retry>,
+    #[serde(default)]
+    overlap: Overlap,
+    #[serde(default)]
+    on_success: Vec<Command>,
+    #[serde(default)]
+    on_failure: Vec<Command>,
+    #[serde(default)]
+    audio: bool,
+    #[serde(default)]
+    tracks: Vec<PathBuf>,
+    #[serde(default)]
+    order: PlaylistOrder,
+    #[serde(default)]
+    volume: Option<f32>,
+    #[serde(default, rename = "loop")]
+    loops: Option<u32>,
+    #[serde(default)]
+    fade_in: Option<f64>,
+    #[serde(default)]
+    fade_out: Option<f64>,
+    #[serde(default)]
+    max_duration: Option<u64>,
+    #[serde(default = "default_notify")]
+    notify: isize,
+}
 
-        let to_milliseconds = 0u32;
-        let to_seconds = self.second as u32;
-        let to_minutes = self.minute as u32;
-        let to_hours = self.hour as u32;
+fn default_notify() -> isize {
+    -1
+}
 
-        let mut milliseconds = to_milliseconds as i32 - from_milliseconds as i32;
-        let mut seconds = to_seconds as i32 - from_seconds as i32;
-        let mut minutes = to_minutes as i32 - from_minutes as i32;
-        let mut hours = to_hours as i32 - from_hours as i32;
+impl TryFrom<RawCommand> for Command {
+    type Error = String;
 
-        while milliseconds < 0 {
-            milliseconds += 1000;
-            seconds -= 1;
+    fn try_from(raw: RawCommand) -> Result<Self, Self::Error> {
+        if raw.command.is_empty() && raw.speak.is_none() && raw.tone.is_none() {
+            return Err("命令需要指定 command、speak 或 tone 字段".to_string());
+        }
+        if let Some(tone) = &raw.tone {
+            tone.check()?;
+        }
+        let audio_options = AudioOptions {
+            volume: raw.volume,
+            loops: raw.loops,
+            fade_in: raw.fade_in,
+            fade_out: raw.fade_out,
+            max_duration: raw.max_duration,
+        };
+        audio_options.check()?;
+        if !raw.overlap.is_allow() && (raw.audio || raw.speak.is_some() || raw.tone.is_some()) {
+            return Err("overlap 只能用于执行命令，不能用于播放音频、朗读或提示音".to_string());
         }
 
-        while seconds < 0 {
-            seconds += 60;
-            minutes -= 1;
+        Ok(Command {
+            command: raw.command,
+            speak: raw.speak,
+            voice: raw.voice,
+            tone: raw.tone,
+            shell: raw.shell,
+            parameters: raw.parameters,
+            args: raw.args,
+            env: raw.env,
+            working_dir: raw.working_dir,
+            wait: raw.wait || raw.timeout.is_some(),
+            timeout: raw.timeout,
+            retry: raw.retry,
+            overlap: raw.overlap,
+            on_success: raw.on_success,
+            on_failure: raw.on_failure,
+            audio: raw.audio,
+            tracks: raw.tracks,
+            order: raw.order,
+            audio_options,
+            notify: raw.notify,
+            source: None,
+        })
+    }
+}
+
+impl Command {
+    pub fn name(&self) -> &str {
+        match (&self.speak, &self.tone) {
+            (Some(text), _) => text,
+            (None, Some(_)) => "提示音",
+            (None, None) => &self.command,
         }
+    }
 
-        while minutes < 0 {
-            minutes += 60;
-            hours -= 1;
+    pub fn render(&self, time: DateTime<Local>, item: &Item) -> Self {
+        let zoned = match &item.timezone {
+            Some(timezone) => time.with_timezone(timezone).naive_local(),
+            None => time.naive_local(),
+        };
+
+        Command {
+            speak: self.speak.as_ref().map(|text| {
+                text.replace("{name}", item.name.as_deref().unwrap_or_default())
+                    .replace("{time}", &zoned.format("%H:%M").to_string())
+                    .replace("{date}", &zoned.format("%Y-%m-%d").to_string())
+                    .replace("{weekday}", weekday_name(zoned.weekday()))
+                    .replace("{tags}", &item.tags.join("、"))
+            }),
+            on_success: self
+                .on_success
+                .iter()
+                .map(|command| command.render(time, item))
+                .collect(),
+            on_failure: self
+                .on_failure
+                .iter()
+                .map(|command| command.render(time, item))
+                .collect(),
+            ..self.clone()
         }
+    }
 
-        while hours < 0 {
-            hours += 24;
+    pub fn notification(&self) -> Self {
+        Command {
+            notify: -2,
+            ..self.clone()
         }
+    }
 
-        Duration::from_millis(
-            (hours * 60 * 60 * 1000 + minutes * 60 * 1000 + seconds * 1000 + milliseconds) as u64,
+    pub fn spawns_process(&self) -> bool {
+        self.wait
+            || !self.overlap.is_allow()
+            || self.shell.is_some()
+            || !self.args.is_empty()
+            || !self.env.is_empty()
+            || self.working_dir.is_some()
+    }
+
+    pub fn process_key(&self) -> String {
+        format!(
+            "{} {}{}",
+            self.shell.as_deref().unwrap_or_default(),
+            self.command,
+            self.arguments_note()
         )
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Command {
-    pub command: String,
-    pub parameters: String,
-    pub audio: bool,
-    pub notify: isize,
+    pub fn arguments(&self) -> Vec<String> {
+        let mut arguments = split_arguments(&self.parameters);
+        arguments.extend(self.args.iter().cloned());
+        arguments
+    }
+
+    pub fn arguments_note(&self) -> String {
+        let arguments = self.arguments();
+        if arguments.is_empty() {
+            String::new()
+        } else {
+            format!("（参数：{}）", join_arguments(&arguments))
+        }
+    }
+
+    pub fn playlist(&self) -> NormalResult<Vec<PathBuf>> {
+        let mut tracks = Vec::new();
+        for path in std::iter::once(Path::new(&self.command))
+            .chain(self.tracks.iter().map(PathBuf::as_path))
+        {
+            tracks.extend(playlist::resolve(path)?);
+        }
+
+        if tracks.is_empty() {
+            return Err(EmptyPlaylist(self.command.clone()));
+        }
+
+        Ok(tracks)
+    }
+
+    pub fn is_single_track(&self) -> bool {
+        self.tracks.is_empty()
+            && matches!(self.playlist().as_deref(), Ok([track]) if track == Path::new(&self.command))
+    }
+
+    pub fn source_note(&self) -> String {
+        match &self.source {
+            Some(source) => format!("（来自 {}）", source.display()),
+            None => String::new(),
+        }
+    }
 }
 
 impl Default for Command {
     fn default() -> Self {
         Command {
             command: String::new(),
+            speak: None,
+            voice: None,
+            tone: None,
+            shell: None,
             parameters: String::new(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            working_dir: None,
+            wait: false,
+            timeout: None,
+            retry: None,
+            overlap: Overlap::default(),
+            on_success: Vec::new(),
+            on_failure: Vec::new(),
             audio: false,
+            tracks: Vec::new(),
+            order: PlaylistOrder::default(),
+            audio_options: AudioOptions::default(),
             notify: -1,
+            source: None,
         }
     }
 }
@@ -328,113 +1283,399 @@ impl Default for Command {
 pub trait AddCommand {
     fn _add_command<R: RangeBounds<usize> + Iterator<Item = usize> + DoubleEndedIterator>(
         &mut self,
-        time: Time,
+        item: Item,
         command: Command,
         range: R,
     ) -> bool;
 
-    fn add_command_with_index(&mut self, time: Time, command: Command, index: usize) -> bool;
+    fn add_command_with_index(&mut self, item: Item, command: Command, index: usize) -> bool;
 
-    fn add_command(&mut self, time: Time, command: Command) -> bool {
-        self.add_command_with_index(time, command, 0)
+    fn add_command(&mut self, item: Item, command: Command) -> bool {
+        self.add_command_with_index(item, command, 0)
     }
 
     fn _add_command_reverse<R: RangeBounds<usize> + Iterator<Item = usize> + DoubleEndedIterator>(
         &mut self,
-        time: Time,
+        item: Item,
         command: Command,
         range: R,
     ) -> bool;
 
     fn add_command_reverse_with_index(
         &mut self,
-        time: Time,
+        item: Item,
         command: Command,
         index: usize,
     ) -> bool {
-        self._add_command_reverse(time, command, 0..index)
+        self._add_command_reverse(item, command, 0..index)
     }
 
-    fn add_command_reverse(&mut self, time: Time, command: Command) -> bool;
+    fn add_command_reverse(&mut self, item: Item, command: Command) -> bool;
 }
 
 impl AddCommand for Vec<Item> {
     fn _add_command<R: RangeBounds<usize> + IntoIterator<Item = usize> + DoubleEndedIterator>(
         &mut self,
-        time: Time,
+        item: Item,
         command: Command,
         range: R,
     ) -> bool {
         for i in range {
-            match self[i].time.cmp(&time) {
+            match self[i].fire_time().cmp(&item.fire_time()) {
                 Ordering::Greater => {
                     self.insert(
                         i,
                         Item {
-                            time,
                             commands: vec![command],
+                            ..item
                         },
                     );
                     return true;
                 }
-                Ordering::Equal => {
+                Ordering::Equal if self[i].same_schedule(&item) => {
                     self[i].commands.push(command);
                     return false;
                 }
-                Ordering::Less => {}
+                _ => {}
             }
         }
         self.push(Item {
-            time,
             commands: vec![command],
+            ..item
         });
 
         true
     }
 
-    fn add_command_with_index(&mut self, time: Time, command: Command, index: usize) -> bool {
-        self._add_command(time, command, index..self.len())
+    fn add_command_with_index(&mut self, item: Item, command: Command, index: usize) -> bool {
+        self._add_command(item, command, index..self.len())
     }
 
     fn _add_command_reverse<
         R: RangeBounds<usize> + Iterator<Item = usize> + DoubleEndedIterator,
     >(
         &mut self,
-        time: Time,
+        item: Item,
         command: Command,
         range: R,
     ) -> bool {
         for i in range.rev() {
-            match self[i].time.cmp(&time) {
+            match self[i].fire_time().cmp(&item.fire_time()) {
                 Ordering::Less => {
                     self.insert(
                         i + 1,
                         Item {
-                            time,
                             commands: vec![command],
+                            ..item
                         },
                     );
                     return true;
                 }
-                Ordering::Equal => {
+                Ordering::Equal if self[i].same_schedule(&item) => {
                     self[i].commands.push(command);
                     return false;
                 }
-                Ordering::Greater => {}
+                _ => {}
             }
         }
         self.insert(
             0,
             Item {
-                time,
                 commands: vec![command],
+                ..item
             },
         );
 
         true
     }
 
-    fn add_command_reverse(&mut self, time: Time, command: Command) -> bool {
-        self.add_command_reverse_with_index(time, command, self.len())
+    fn add_command_reverse(&mut self, item: Item, command: Command) -> bool {
+        self.add_command_reverse_with_index(item, command, self.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Local> {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Local)
+    }
+
+    fn entries(yaml: &str) -> Vec<Entry> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn new_york(time: Time, repeat: Option<Repeat>) -> Item {
+        Item {
+            time,
+            timezone: Some(chrono_tz::America::New_York),
+            repeat,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn next_breaks_ties_by_index_then_moves_on() {
+        let mut config = Config::new(entries(
+            "
+- time: { hour: 8, minute: 0, second: 0 }
+  timezone: UTC
+  commands: [{ command: a }]
+- cron: 0 0 8 * * *
+  timezone: UTC
+  commands: [{ command: b }]
+",
+        ))
+        .unwrap();
+        config.cursor = at("2026-01-01T07:00:00Z");
+
+        let mut next = || config.next().map(|(index, _, deadline)| (index, deadline));
+        assert_eq!(next(), Some((0, at("2026-01-01T08:00:00Z"))));
+        assert_eq!(next(), Some((1, at("2026-01-01T08:00:00Z"))));
+        assert_eq!(next(), Some((0, at("2026-01-02T08:00:00Z"))));
+        assert_eq!(next(), Some((1, at("2026-01-02T08:00:00Z"))));
+    }
+
+    #[test]
+    fn next_after_moves_times_in_a_dst_gap_forward() {
+        let item = new_york(
+            Time {
+                hour: 2,
+                minute: 30,
+                second: 0,
+            },
+            None,
+        );
+
+        assert_eq!(
+            item.next_after(at("2026-03-08T05:00:00Z"), false),
+            Some(at("2026-03-08T07:00:00Z"))
+        );
+        assert_eq!(
+            item.next_after(at("2026-03-08T07:00:00Z"), false),
+            Some(at("2026-03-09T06:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_after_runs_once_in_a_dst_overlap() {
+        let item = new_york(
+            Time {
+                hour: 1,
+                minute: 30,
+                second: 0,
+            },
+            None,
+        );
+
+        assert_eq!(
+            item.next_after(at("2026-11-01T04:00:00Z"), false),
+            Some(at("2026-11-01T05:30:00Z"))
+        );
+        assert_eq!(
+            item.next_after(at("2026-11-01T05:30:00Z"), false),
+            Some(at("2026-11-02T06:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_after_repeats_across_a_dst_gap() {
+        let item = new_york(
+            Time {
+                hour: 0,
+                minute: 0,
+                second: 0,
+            },
+            Some(Repeat {
+                every: 1800,
+                until: Time {
+                    hour: 23,
+                    minute: 30,
+                    second: 0,
+                },
+            }),
+        );
+
+        assert_eq!(
+            item.next_after(at("2026-03-08T06:59:59Z"), false),
+            Some(at("2026-03-08T07:00:00Z"))
+        );
+        assert_eq!(
+            item.next_after(at("2026-03-08T07:00:00Z"), false),
+            Some(at("2026-03-08T07:30:00Z"))
+        );
+        assert_eq!(
+            item.next_after(at("2026-03-08T07:00:00Z"), true),
+            Some(at("2026-03-08T07:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn take_missed_collapses_each_task() {
+        let mut config = Config::new(entries(
+            "
+- time: { hour: 8, minute: 0, second: 0 }
+  timezone: UTC
+  commands: [{ command: a }]
+- time: { hour: 20, minute: 0, second: 0 }
+  timezone: UTC
+  commands: [{ command: b }]
+",
+        ))
+        .unwrap();
+        config.cursor = at("2026-01-01T09:00:00Z");
+
+        let missed = config.take_missed(at("2026-01-05T09:00:00Z"));
+        let summary: Vec<_> = missed
+            .iter()
+            .map(|missed| (missed.index, missed.count, missed.last))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0, 4, at("2026-01-05T08:00:00Z")),
+                (1, 4, at("2026-01-04T20:00:00Z"))
+            ]
+        );
+        assert!(config.take_missed(at("2026-01-05T09:00:00Z")).is_empty());
+        assert_eq!(
+            config.next().map(|(index, _, deadline)| (index, deadline)),
+            Some((1, at("2026-01-05T20:00:00Z")))
+        );
+    }
+
+    #[test]
+    fn entries_report_errors_of_the_matching_type() {
+        let error = |yaml: &str| {
+            serde_yaml::from_str::<Vec<Entry>>(yaml)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(error(
+            "
+- time: { hour: 8, minute: 0, second: 0 }
+  reccurence: { weekdays: [mon] }
+  commands: [{ command: a }]
+"
+        )
+        .contains("reccurence"));
+        assert!(error(
+            "
+- time: { hour: 8, minute: 0, second: 0 }
+  commands: [{ tone: { pattern: beep-boop } }]
+"
+        )
+        .contains("boop"));
+        assert!(error("- cron: 0 0 8 * * *\n  commands: [{ args: [a] }]\n")
+            .contains("command、speak 或 tone"));
+    }
+
+    #[test]
+    fn commands_reject_unknown_fields() {
+        for field in ["tiemout: 10", "on_falure: []", "volum: 0.5"] {
+            let error = serde_yaml::from_str::<Vec<Entry>>(&format!(
+                "- time: {{ hour: 8, minute: 0, second: 0 }}\n  commands: [{{ command: a, {field} }}]\n"
+            ))
+            .unwrap_err()
+            .to_string();
+            assert!(error.contains(field.split(':').next().unwrap()), "{error}");
+        }
+
+        let command: Command =
+            serde_yaml::from_str("{ command: a, loop: 2, volume: 0.5 }").unwrap();
+        assert_eq!(command.audio_options.loops, Some(2));
+        assert_eq!(command.audio_options.volume, Some(0.5));
+    }
+
+    #[test]
+    fn notifications_keep_whole_repeat_windows() {
+        let mut config = Config::new(entries(
+            "
+- time: { hour: 0, minute: 0, second: 0 }
+  timezone: UTC
+  repeat: { every: 900, until: { hour: 1, minute: 0, second: 0 } }
+  commands: [{ command: a, notify: 60 }]
+",
+        ))
+        .unwrap();
+        config.parse_notification();
+        config.cursor = at("2026-01-01T23:00:00Z");
+
+        let notifications: Vec<_> = std::iter::from_fn(|| config.next())
+            .take(10)
+            .filter(|(_, item, _)| item.advance > TimeDelta::zero())
+            .map(|(_, _, deadline)| deadline)
+            .collect();
+        assert_eq!(
+            notifications,
+            [
+                at("2026-01-01T23:59:00Z"),
+                at("2026-01-02T00:14:00Z"),
+                at("2026-01-02T00:29:00Z"),
+                at("2026-01-02T00:44:00Z"),
+                at("2026-01-02T00:59:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeat_windows_cannot_cross_midnight() {
+        let error = serde_yaml::from_str::<Vec<Entry>>(
+            "
+- time: { hour: 22, minute: 0, second: 0 }
+  repeat: { every: 900, until: { hour: 2, minute: 0, second: 0 } }
+  commands: [{ command: a }]
+",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("until"), "{error}");
+    }
+
+    #[test]
+    fn dates_follow_the_item_timezone() {
+        let item = Item {
+            timezone: Some(chrono_tz::Asia::Shanghai),
+            ..Default::default()
+        };
+        assert_eq!(
+            item.date(at("2026-10-01T23:00:00Z")),
+            NaiveDate::from_ymd_opt(2026, 10, 2).unwrap()
+        );
+
+        let notification = Item {
+            advance: TimeDelta::seconds(120),
+            ..item
+        };
+        assert_eq!(
+            notification.date(at("2026-10-01T15:59:00Z")),
+            NaiveDate::from_ymd_opt(2026, 10, 2).unwrap()
+        );
+    }
+
+    #[test]
+    fn render_uses_the_item_timezone() {
+        let item = Item {
+            name: Some("早会".to_string()),
+            timezone: Some(chrono_tz::America::New_York),
+            tags: vec!["work".to_string()],
+            ..Default::default()
+        };
+        let command = Command {
+            speak: Some("{name} {date} {weekday} {time} {tags}".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            command
+                .render(at("2026-10-20T01:30:00Z"), &item)
+                .speak
+                .as_deref(),
+            Some("早会 2026-10-19 周一 21:30 work")
+        );
     }
 }
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Duration, Local, TimeZone};
+use std::str::FromStr;
+
+use chrono_tz::Tz;
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{FinalResult, UnexpectedError};
+use crate::structs::config::Command;
+use crate::structs::misfire::Misfire;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CronItem {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub cron: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub misfire: Option<Misfire>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sequential: bool,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CronTask {
+    pub item: CronItem,
+    pub advance: Duration,
+    schedule: Schedule,
+}
+
+impl CronTask {
+    pub fn new(item: CronItem) -> FinalResult<Self> {
+        let schedule = Schedule::from_str(&item.cron)
+            .map_err(|e| UnexpectedError::Cron(item.cron.clone(), e))?;
+
+        Ok(CronTask {
+            item,
+            advance: Duration::zero(),
+            schedule,
+        })
+    }
+
+    pub fn advanced(&self, seconds: usize, commands: Vec<Command>) -> Self {
+        CronTask {
+            item: CronItem {
+                commands,
+                ..self.item.clone()
+            },
+            advance: Duration::seconds(seconds as i64),
+            schedule: self.schedule.clone(),
+        }
+    }
+
+    pub fn next_after(&self, after: DateTime<Local>, inclusive: bool) -> Option<DateTime<Local>> {
+        self.times_after(after - Duration::seconds(1))
+            .find(|time| *time > after || (inclusive && *time == after))
+    }
+
+    pub fn upcoming(&self, count: usize) -> Vec<DateTime<Local>> {
+        self.times_after(Local::now()).take(count).collect()
+    }
+
+    fn times_after(
+        &self,
+        after: DateTime<Local>,
+    ) -> Box<dyn Iterator<Item = DateTime<Local>> + '_> {
+        match &self.item.timezone {
+            Some(timezone) => Box::new(self.times_in(timezone, after)),
+            None => Box::new(self.times_in(&Local, after)),
+        }
+    }
+
+    fn times_in<'a, Z: TimeZone + 'a>(
+        &'a self,
+        timezone: &Z,
+        after: DateTime<Local>,
+    ) -> impl Iterator<Item = DateTime<Local>> + 'a {
+        self.schedule
+            .after(&(after + self.advance).with_timezone(timezone))
+            .map(|time| time.with_timezone(&Local) - self.advance)
+    }
+}
@@ -0,0 +1,79 @@
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::path::PathBuf;
+
+use crate::error::{FinalResult, UnexpectedError};
+use crate::structs::audio_mode::AudioMode;
+use crate::structs::config::Entry;
+use crate::structs::misfire::Misfire;
+
+pub const CURRENT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Settings::is_empty")]
+    pub settings: Settings,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub misfire: Option<Misfire>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_processes: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_mode: Option<AudioMode>,
+}
+
+impl Document {
+    pub fn new(items: Vec<Entry>) -> Self {
+        Document {
+            version: CURRENT_VERSION,
+            settings: Settings::default(),
+            items,
+        }
+    }
+
+    pub fn parse(content: &str) -> FinalResult<(Self, bool)> {
+        let value = serde_yaml::from_str::<Value>(content)?;
+
+        match value {
+            Value::Null => Ok((Document::new(Vec::new()), false)),
+            Value::Sequence(_) => Ok((Document::new(serde_yaml::from_value(value)?), true)),
+            _ => {
+                let document = serde_yaml::from_value::<Document>(value)?;
+                if document.version > CURRENT_VERSION {
+                    return Err(UnexpectedError::Version(document.version));
+                }
+
+                Ok((document, false))
+            }
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_empty(&self) -> bool {
+        self.misfire.is_none()
+            && self.timezone.is_none()
+            && self.log.is_none()
+            && self.max_processes.is_none()
+            && self.audio_mode.is_none()
+    }
+
+    pub fn merge(&mut self, other: Settings) {
+        self.misfire = self.misfire.or(other.misfire);
+        self.timezone = self.timezone.or(other.timezone);
+        self.log = self.log.take().or(other.log);
+        self.max_processes = self.max_processes.or(other.max_processes);
+        self.audio_mode = self.audio_mode.or(other.audio_mode);
+    }
+}
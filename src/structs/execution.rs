@@ -0,0 +1,75 @@
+use chrono::{DateTime, Local};
+use std::fmt::Display;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::FinalResult;
+use crate::structs::config::Command;
+
+#[derive(Debug)]
+pub struct Execution {
+    pub command: Command,
+    pub started: DateTime<Local>,
+    pub duration: Duration,
+    pub code: Option<i32>,
+    pub timed_out: bool,
+    pub cancelled: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Execution {
+    pub fn succeeded(&self) -> bool {
+        !self.timed_out && !self.cancelled && self.code == Some(0)
+    }
+
+    pub fn append_to(&self, path: &Path) -> FinalResult {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(self.to_string().as_bytes())?;
+
+        Ok(())
+    }
+}
+
+impl Display for Execution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "[{}] 命令 {}{}{}",
+            self.started.format("%Y-%m-%d %H:%M:%S"),
+            self.command.command,
+            self.command.arguments_note(),
+            self.command.source_note()
+        )?;
+
+        let status = match (self.timed_out, self.cancelled, self.code) {
+            (true, _, _) => "超时，已终止".to_string(),
+            (_, true, _) => "已被新的运行终止".to_string(),
+            (false, false, Some(code)) => code.to_string(),
+            (false, false, None) => "被信号终止".to_string(),
+        };
+        writeln!(
+            f,
+            "退出状态：{}，用时：{:.3} 秒",
+            status,
+            self.duration.as_secs_f64()
+        )?;
+
+        for (name, output) in [("标准输出", &self.stdout), ("标准错误", &self.stderr)] {
+            if !output.is_empty() {
+                writeln!(f, "{}：", name)?;
+                write!(f, "{}", output)?;
+                if !output.ends_with('\n') {
+                    writeln!(f)?;
+                }
+            }
+        }
+
+        writeln!(f)
+    }
+}
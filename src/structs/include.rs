@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{FinalResult, UnexpectedError};
+use crate::structs::config::Entry;
+use crate::structs::document::{Document, Settings};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IncludeReference {
+    pub include: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Source {
+    pub path: PathBuf,
+    pub settings: Settings,
+    pub entries: Vec<Entry>,
+}
+
+pub fn load_sources(paths: &[PathBuf]) -> FinalResult<Vec<Source>> {
+    let mut sources = Vec::new();
+    let mut visited = HashSet::new();
+
+    for path in paths {
+        load_source(path, &mut visited, &mut sources)?;
+    }
+
+    Ok(sources)
+}
+
+fn load_source(
+    path: &Path,
+    visited: &mut HashSet<PathBuf>,
+    sources: &mut Vec<Source>,
+) -> FinalResult {
+    if !visited.insert(fs::canonicalize(path)?) {
+        return Ok(());
+    }
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let (document, legacy) = Document::parse(&fs::read_to_string(path)?)?;
+    if legacy {
+        println!(
+            "配置文件 {} 使用旧版格式，可以使用 format 命令将其升级为新版格式",
+            path.display()
+        );
+    }
+
+    let mut settings = document.settings;
+    if let Some(log) = &mut settings.log {
+        *log = directory.join(&log);
+    }
+
+    let mut entries = document.items;
+    let mut patterns = Vec::new();
+
+    for entry in &mut entries {
+        match entry {
+            Entry::Calendar(reference) => reference.calendar = directory.join(&reference.calendar),
+            Entry::Include(reference) => patterns.extend(reference.include.iter().cloned()),
+            _ => {}
+        }
+    }
+
+    sources.push(Source {
+        path: path.to_path_buf(),
+        settings,
+        entries,
+    });
+
+    for pattern in patterns {
+        let matches = glob::glob(&directory.join(&pattern).to_string_lossy())
+            .map_err(|e| UnexpectedError::Include(pattern.clone(), e))?;
+
+        let mut found = false;
+        for matched in matches {
+            found = true;
+            load_source(&matched.map_err(std::io::Error::from)?, visited, sources)?;
+        }
+
+        if !found {
+            eprintln!("包含的路径 {} 没有匹配到任何文件", pattern);
+        }
+    }
+
+    Ok(())
+}
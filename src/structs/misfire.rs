@@ -0,0 +1,92 @@
+use chrono::{DateTime, Local};
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::args;
+
+const DEFAULT_GRACE_SECONDS: u64 = 300;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Misfire {
+    pub policy: MisfirePolicy,
+    #[serde(default = "default_grace")]
+    pub grace: u64,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    #[default]
+    Skip,
+    RunOnce,
+    Grace,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Missed {
+    pub index: usize,
+    pub count: usize,
+    pub exact: bool,
+    pub last: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MisfireOverrides {
+    pub policy: Option<MisfirePolicy>,
+    pub grace: Option<u64>,
+}
+
+impl MisfireOverrides {
+    pub fn apply(&self, misfire: Misfire) -> Misfire {
+        Misfire {
+            policy: self.policy.unwrap_or(misfire.policy),
+            grace: self.grace.unwrap_or(misfire.grace),
+        }
+    }
+}
+
+impl Default for Misfire {
+    fn default() -> Self {
+        Misfire {
+            policy: MisfirePolicy::default(),
+            grace: DEFAULT_GRACE_SECONDS,
+        }
+    }
+}
+
+impl From<args::MisfirePolicy> for MisfirePolicy {
+    fn from(policy: args::MisfirePolicy) -> Self {
+        match policy {
+            args::MisfirePolicy::Skip => MisfirePolicy::Skip,
+            args::MisfirePolicy::RunOnce => MisfirePolicy::RunOnce,
+            args::MisfirePolicy::Grace => MisfirePolicy::Grace,
+        }
+    }
+}
+
+impl Display for Misfire {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.policy {
+            MisfirePolicy::Skip => write!(f, "跳过"),
+            MisfirePolicy::RunOnce => write!(f, "立即执行一次"),
+            MisfirePolicy::Grace => write!(f, "错过 {} 秒以内时立即执行，否则跳过", self.grace),
+        }
+    }
+}
+
+impl Display for Missed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "已错过{} {} 次，最近一次为 {}",
+            if self.exact { "" } else { "至少" },
+            self.count,
+            self.last.format("%Y-%m-%d %H:%M:%S")
+        )
+    }
+}
+
+fn default_grace() -> u64 {
+    DEFAULT_GRACE_SECONDS
+}
@@ -1 +1,17 @@
+pub mod audio_mode;
+pub mod audio_options;
+pub mod calendar;
 pub mod config;
+pub mod cron_item;
+pub mod document;
+pub mod execution;
+pub mod include;
+pub mod misfire;
+pub mod once;
+pub mod overlap;
+pub mod playlist;
+pub mod recurrence;
+pub mod repeat;
+pub mod retry;
+pub mod state;
+pub mod tone;
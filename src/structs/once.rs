@@ -0,0 +1,50 @@
+use chrono::NaiveDateTime;
+use std::path::{Path, PathBuf};
+
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::structs::config::{Command, Item, Time};
+use crate::structs::misfire::Misfire;
+use crate::structs::recurrence::Recurrence;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OnceItem {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub at: NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub misfire: Option<Misfire>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sequential: bool,
+    pub commands: Vec<Command>,
+}
+
+impl From<OnceItem> for Item {
+    fn from(item: OnceItem) -> Self {
+        Item {
+            name: item.name,
+            time: Time::from(item.at.time()),
+            timezone: item.timezone,
+            recurrence: Some(Recurrence {
+                dates: vec![item.at.date()],
+                ..Default::default()
+            }),
+            tags: item.tags,
+            misfire: item.misfire,
+            sequential: item.sequential,
+            commands: item.commands,
+            once: Some(item.at),
+            ..Default::default()
+        }
+    }
+}
+
+pub fn once_path(config_path: &Path) -> PathBuf {
+    config_path.with_extension("once.yaml")
+}
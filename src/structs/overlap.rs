@@ -0,0 +1,30 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overlap {
+    #[default]
+    Allow,
+    Skip,
+    Queue,
+    KillPrevious,
+}
+
+impl Overlap {
+    pub fn is_allow(&self) -> bool {
+        *self == Overlap::Allow
+    }
+}
+
+impl Display for Overlap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Overlap::Allow => write!(f, "同时运行"),
+            Overlap::Skip => write!(f, "跳过本次运行"),
+            Overlap::Queue => write!(f, "等待上一次运行结束"),
+            Overlap::KillPrevious => write!(f, "终止上一次运行"),
+        }
+    }
+}
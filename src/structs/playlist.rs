@@ -0,0 +1,167 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::NormalError::Playlist;
+use crate::error::NormalResult;
+
+const AUDIO_EXTENSIONS: [&str; 4] = ["wav", "mp3", "ogg", "flac"];
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistOrder {
+    #[default]
+    InOrder,
+    Shuffle,
+    Random,
+}
+
+impl PlaylistOrder {
+    pub fn is_in_order(&self) -> bool {
+        *self == PlaylistOrder::InOrder
+    }
+
+    pub fn select(&self, mut tracks: Vec<PathBuf>) -> Vec<PathBuf> {
+        match self {
+            PlaylistOrder::InOrder => tracks,
+            PlaylistOrder::Shuffle => {
+                fastrand::shuffle(&mut tracks);
+                tracks
+            }
+            PlaylistOrder::Random if tracks.is_empty() => tracks,
+            PlaylistOrder::Random => vec![tracks.swap_remove(fastrand::usize(..tracks.len()))],
+        }
+    }
+}
+
+impl Display for PlaylistOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlaylistOrder::InOrder => write!(f, "按顺序播放"),
+            PlaylistOrder::Shuffle => write!(f, "打乱顺序播放"),
+            PlaylistOrder::Random => write!(f, "随机播放其中一首"),
+        }
+    }
+}
+
+pub fn resolve(path: &Path) -> NormalResult<Vec<PathBuf>> {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if path.is_dir() {
+        let mut tracks = fs::read_dir(path)
+            .map_err(|e| Playlist(path.to_path_buf(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|track| track.is_file() && is_audio(track))
+            .collect::<Vec<_>>();
+        tracks.sort();
+        return Ok(tracks);
+    }
+
+    let parse: fn(&str) -> Option<&str> = match extension.as_str() {
+        "m3u" | "m3u8" => |line| (!line.starts_with('#')).then_some(line),
+        "pls" => |line| {
+            line.split_once('=')
+                .filter(|(key, _)| key.to_lowercase().starts_with("file"))
+                .map(|(_, value)| value)
+        },
+        _ => return Ok(vec![path.to_path_buf()]),
+    };
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let content = fs::read_to_string(path).map_err(|e| Playlist(path.to_path_buf(), e))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(parse)
+        .map(|track| directory.join(track.trim()))
+        .collect())
+}
+
+fn is_audio(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        AUDIO_EXTENSIONS.contains(&extension.to_string_lossy().to_lowercase().as_str())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("boom_you_up_r-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn resolve_reads_m3u_and_pls() {
+        let directory = directory("lists");
+        let m3u = directory.join("list.M3U");
+        fs::write(&m3u, "#EXTM3U\n#EXTINF:1,a\na.mp3\n\n  sub/b.ogg  \n").unwrap();
+        let pls = directory.join("list.pls");
+        fs::write(
+            &pls,
+            "[playlist]\nFile1=a.mp3\nTitle1=A\nfile2 = /music/c.flac\nNumberOfEntries=2\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            resolve(&m3u).unwrap(),
+            [directory.join("a.mp3"), directory.join("sub/b.ogg")]
+        );
+        assert_eq!(
+            resolve(&pls).unwrap(),
+            [directory.join("a.mp3"), PathBuf::from("/music/c.flac")]
+        );
+        assert_eq!(
+            resolve(&directory.join("single.mp3")).unwrap(),
+            [directory.join("single.mp3")]
+        );
+        assert!(resolve(&directory.join("missing.m3u")).is_err());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn resolve_lists_audio_files_in_directories() {
+        let directory = directory("tracks");
+        for name in ["b.MP3", "a.wav", "notes.txt", "c.flac"] {
+            fs::write(directory.join(name), "").unwrap();
+        }
+        fs::create_dir(directory.join("d.ogg")).unwrap();
+
+        assert_eq!(
+            resolve(&directory).unwrap(),
+            [
+                directory.join("a.wav"),
+                directory.join("b.MP3"),
+                directory.join("c.flac")
+            ]
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn random_selects_a_single_track() {
+        let tracks: Vec<_> = ["a", "b", "c"].map(PathBuf::from).into();
+
+        let selected = PlaylistOrder::Random.select(tracks.clone());
+        assert_eq!(selected.len(), 1);
+        assert!(tracks.contains(&selected[0]));
+        assert!(PlaylistOrder::Random.select(Vec::new()).is_empty());
+
+        let mut shuffled = PlaylistOrder::Shuffle.select(tracks.clone());
+        shuffled.sort();
+        assert_eq!(shuffled, tracks);
+    }
+}
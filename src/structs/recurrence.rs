@@ -0,0 +1,254 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::fmt::Display;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+#[derive(Debug, Eq, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct Recurrence {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weekdays: Vec<Weekday>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dates: Vec<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every: Option<Every>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranges: Vec<DateRange>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Every {
+    #[serde(deserialize_with = "positive")]
+    pub days: u32,
+    pub from: NaiveDate,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl Recurrence {
+    pub fn is_daily(&self) -> bool {
+        self.weekdays.is_empty()
+            && self.dates.is_empty()
+            && self.every.is_none()
+            && self.ranges.is_empty()
+    }
+
+    pub fn runs_on(&self, date: NaiveDate) -> bool {
+        if self.dates.contains(&date) {
+            return true;
+        }
+
+        if !self.dates.is_empty()
+            && self.weekdays.is_empty()
+            && self.every.is_none()
+            && self.ranges.is_empty()
+        {
+            return false;
+        }
+
+        (self.weekdays.is_empty() || self.weekdays.contains(&date.weekday()))
+            && self.every.is_none_or(|every| every.contains(date))
+            && (self.ranges.is_empty() || self.ranges.iter().any(|range| range.contains(date)))
+    }
+
+    pub fn shifted(&self, days: i64) -> Self {
+        let shift = |date: NaiveDate| date + Duration::days(days);
+
+        Recurrence {
+            weekdays: self
+                .weekdays
+                .iter()
+                .map(|weekday| {
+                    let mut weekday = *weekday;
+                    for _ in 0..days.rem_euclid(7) {
+                        weekday = weekday.succ();
+                    }
+                    weekday
+                })
+                .collect(),
+            dates: self.dates.iter().copied().map(shift).collect(),
+            every: self.every.map(|every| Every {
+                from: shift(every.from),
+                ..every
+            }),
+            ranges: self
+                .ranges
+                .iter()
+                .map(|range| DateRange {
+                    from: shift(range.from),
+                    to: shift(range.to),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_daily() {
+            return write!(f, "每天");
+        }
+
+        let mut parts = Vec::new();
+
+        if !self.weekdays.is_empty() {
+            parts.push(format!(
+                "每{}",
+                self.weekdays
+                    .iter()
+                    .map(|weekday| weekday_name(*weekday))
+                    .collect::<Vec<_>>()
+                    .join("、")
+            ));
+        }
+
+        if let Some(every) = self.every {
+            parts.push(format!("自 {} 起每 {} 天", every.from, every.days));
+        }
+
+        if !self.ranges.is_empty() {
+            parts.push(format!(
+                "在 {} 期间",
+                self.ranges
+                    .iter()
+                    .map(|range| format!("{} 至 {}", range.from, range.to))
+                    .collect::<Vec<_>>()
+                    .join("、")
+            ));
+        }
+
+        if !self.dates.is_empty() {
+            parts.push(format!(
+                "{}在 {}",
+                if parts.is_empty() { "" } else { "以及" },
+                self.dates
+                    .iter()
+                    .map(|date| date.to_string())
+                    .collect::<Vec<_>>()
+                    .join("、")
+            ));
+        }
+
+        write!(f, "{}", parts.join("，"))
+    }
+}
+
+impl Every {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        let days = (date - self.from).num_days();
+        days >= 0 && days % i64::from(self.days) == 0
+    }
+}
+
+impl DateRange {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from <= date && date <= self.to
+    }
+}
+
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "周一",
+        Weekday::Tue => "周二",
+        Weekday::Wed => "周三",
+        Weekday::Thu => "周四",
+        Weekday::Fri => "周五",
+        Weekday::Sat => "周六",
+        Weekday::Sun => "周日",
+    }
+}
+
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    match u32::deserialize(deserializer)? {
+        0 => Err(de::Error::custom("recurrence 中的 days 必须大于 0")),
+        days => Ok(days),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn runs_on_weekdays_and_extra_dates() {
+        let recurrence = Recurrence {
+            weekdays: vec![Weekday::Mon],
+            dates: vec![date("2026-10-21")],
+            ..Default::default()
+        };
+
+        assert!(recurrence.runs_on(date("2026-10-19")));
+        assert!(!recurrence.runs_on(date("2026-10-20")));
+        assert!(recurrence.runs_on(date("2026-10-21")));
+        assert!(recurrence.runs_on(date("2026-10-26")));
+    }
+
+    #[test]
+    fn runs_on_dates_only() {
+        let recurrence = Recurrence {
+            dates: vec![date("2026-10-21")],
+            ..Default::default()
+        };
+
+        assert!(recurrence.runs_on(date("2026-10-21")));
+        assert!(!recurrence.runs_on(date("2026-10-22")));
+    }
+
+    #[test]
+    fn runs_on_every_within_ranges() {
+        let recurrence = Recurrence {
+            every: Some(Every {
+                days: 2,
+                from: date("2026-01-01"),
+            }),
+            ranges: vec![DateRange {
+                from: date("2026-01-03"),
+                to: date("2026-01-07"),
+            }],
+            ..Default::default()
+        };
+
+        let days: Vec<_> = date("2025-12-30")
+            .iter_days()
+            .take(12)
+            .filter(|day| recurrence.runs_on(*day))
+            .collect();
+        assert_eq!(
+            days,
+            [date("2026-01-03"), date("2026-01-05"), date("2026-01-07")]
+        );
+    }
+
+    #[test]
+    fn shifted_moves_every_rule() {
+        let recurrence = Recurrence {
+            weekdays: vec![Weekday::Mon, Weekday::Thu],
+            every: Some(Every {
+                days: 3,
+                from: date("2026-01-01"),
+            }),
+            ranges: vec![DateRange {
+                from: date("2026-01-01"),
+                to: date("2026-03-01"),
+            }],
+            dates: vec![date("2026-04-01")],
+        };
+        let shifted = recurrence.shifted(-1);
+
+        assert_eq!(shifted.weekdays, [Weekday::Sun, Weekday::Wed]);
+        for day in date("2025-12-25").iter_days().take(120) {
+            assert_eq!(
+                shifted.runs_on(day - Duration::days(1)),
+                recurrence.runs_on(day),
+                "{day}"
+            );
+        }
+    }
+}
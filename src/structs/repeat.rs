@@ -0,0 +1,47 @@
+use std::fmt::Display;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::structs::config::Time;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Repeat {
+    #[serde(deserialize_with = "positive")]
+    pub every: u32,
+    pub until: Time,
+}
+
+impl Repeat {
+    pub fn shifted(&self, seconds: usize) -> Self {
+        Repeat {
+            until: self.until - Time::second(seconds),
+            ..*self
+        }
+    }
+}
+
+impl Display for Repeat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (hours, minutes, seconds) =
+            (self.every / 3600, self.every % 3600 / 60, self.every % 60);
+
+        write!(f, "每")?;
+        if hours > 0 {
+            write!(f, " {} 小时", hours)?;
+        }
+        if minutes > 0 {
+            write!(f, " {} 分钟", minutes)?;
+        }
+        if seconds > 0 {
+            write!(f, " {} 秒", seconds)?;
+        }
+        write!(f, "执行一次，直到 {}", self.until)
+    }
+}
+
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    match u32::deserialize(deserializer)? {
+        0 => Err(de::Error::custom("repeat 中的 every 必须大于 0")),
+        every => Ok(every),
+    }
+}
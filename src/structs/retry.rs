@@ -0,0 +1,71 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_BACKOFF_SECONDS: u64 = 10;
+const MAX_BACKOFF_DOUBLINGS: u32 = 16;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Retry {
+    pub attempts: u32,
+    #[serde(default = "default_backoff")]
+    pub backoff: u64,
+}
+
+impl Retry {
+    pub fn delay(&self, retried: u32) -> Duration {
+        Duration::from_secs(
+            self.backoff
+                .saturating_mul(1 << retried.min(MAX_BACKOFF_DOUBLINGS)),
+        )
+    }
+}
+
+impl Display for Retry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "最多重试 {} 次，首次等待 {} 秒，之后每次翻倍",
+            self.attempts, self.backoff
+        )
+    }
+}
+
+fn default_backoff() -> u64 {
+    DEFAULT_BACKOFF_SECONDS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_doubles_up_to_a_limit() {
+        let retry = Retry {
+            attempts: 3,
+            backoff: 10,
+        };
+
+        let delays: Vec<_> = (0..4)
+            .map(|retried| retry.delay(retried).as_secs())
+            .collect();
+        assert_eq!(delays, [10, 20, 40, 80]);
+        assert_eq!(retry.delay(100), retry.delay(MAX_BACKOFF_DOUBLINGS));
+        assert_eq!(
+            Retry {
+                backoff: u64::MAX,
+                ..retry
+            }
+            .delay(1)
+            .as_secs(),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn backoff_defaults_to_ten_seconds() {
+        let retry: Retry = serde_yaml::from_str("attempts: 2").unwrap();
+        assert_eq!(retry.delay(0), Duration::from_secs(DEFAULT_BACKOFF_SECONDS));
+    }
+}
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Local};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::FinalResult;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub last_fired: BTreeMap<String, DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub done: BTreeMap<String, DateTime<Local>>,
+}
+
+impl State {
+    pub fn load(path: &Path) -> FinalResult<Self> {
+        if !path.exists() {
+            return Ok(State::default());
+        }
+
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> FinalResult {
+        fs::write(path, serde_yaml::to_string(self)?)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use std::f64::consts::TAU;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+const SAMPLE_RATE: u32 = 44100;
+const AMPLITUDE: f64 = 0.5;
+const EDGE_SECONDS: f64 = 0.005;
+const MAX_SECONDS: f64 = 600.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tone {
+    #[serde(default, skip_serializing_if = "Waveform::is_sine")]
+    pub wave: Waveform,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<u32>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Square,
+}
+
+impl Waveform {
+    pub fn is_sine(&self) -> bool {
+        *self == Waveform::Sine
+    }
+
+    fn sample(&self, phase: f64) -> f64 {
+        match self {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square if phase < 0.5 => 1.0,
+            Waveform::Square => -1.0,
+        }
+    }
+}
+
+impl Tone {
+    pub fn frequency(&self) -> f64 {
+        self.frequency.unwrap_or(880.0)
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.duration.unwrap_or(0.2).max(0.0)
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_deref().unwrap_or("beep")
+    }
+
+    pub fn repeat(&self) -> u32 {
+        self.repeat.unwrap_or(1)
+    }
+
+    pub fn segments(&self) -> Result<Vec<bool>, String> {
+        self.pattern()
+            .split('-')
+            .map(|segment| match segment.trim() {
+                "beep" => Ok(true),
+                "pause" => Ok(false),
+                _ => Err(format!(
+                    "提示音模式 {} 中的 {} 无法识别，只能使用 beep 和 pause",
+                    self.pattern(),
+                    segment
+                )),
+            })
+            .collect()
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        let seconds = self.duration() * self.segments()?.len() as f64 * self.repeat() as f64;
+        if seconds > MAX_SECONDS {
+            return Err(format!(
+                "提示音总时长为 {} 秒，超过了 {} 秒的上限",
+                seconds, MAX_SECONDS
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn wav(&self) -> Vec<u8> {
+        let segments = self.segments().unwrap_or_default();
+        let length = (self.duration() * SAMPLE_RATE as f64) as usize;
+        let edge = EDGE_SECONDS * SAMPLE_RATE as f64;
+
+        let mut samples = Vec::new();
+        for _ in 0..self.repeat() {
+            for &beep in &segments {
+                samples.extend((0..length).map(|index| {
+                    if !beep {
+                        return 0;
+                    }
+
+                    let phase = (index as f64 * self.frequency() / SAMPLE_RATE as f64).fract();
+                    let envelope = (index as f64 / edge)
+                        .min((length - index) as f64 / edge)
+                        .min(1.0);
+                    (self.wave.sample(phase) * envelope * AMPLITUDE * i16::MAX as f64) as i16
+                }));
+            }
+        }
+
+        encode_wav(&samples)
+    }
+}
+
+impl Display for Tone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} Hz {}，每段 {} 秒，{}",
+            self.frequency(),
+            match self.wave {
+                Waveform::Sine => "正弦波",
+                Waveform::Square => "方波",
+            },
+            self.duration(),
+            self.pattern()
+        )?;
+
+        if self.repeat() != 1 {
+            write!(f, "，重复 {} 次", self.repeat())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn encode_wav(samples: &[i16]) -> Vec<u8> {
+    let data_length = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_length as usize);
+
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_length).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_length.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(pattern: &str, repeat: u32) -> Tone {
+        Tone {
+            duration: Some(0.1),
+            pattern: Some(pattern.to_string()),
+            repeat: Some(repeat),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn segments_follow_the_pattern() {
+        assert_eq!(
+            tone("beep-pause- beep", 1).segments(),
+            Ok(vec![true, false, true])
+        );
+        assert!(tone("beep-boop", 1)
+            .segments()
+            .unwrap_err()
+            .contains("boop"));
+    }
+
+    #[test]
+    fn check_caps_the_total_length() {
+        assert!(tone("beep-pause", 3000).check().is_ok());
+        assert!(tone("beep-pause", 3001).check().is_err());
+        assert!(tone("buzz", 1).check().is_err());
+    }
+
+    #[test]
+    fn wav_encodes_every_segment() {
+        let wav = tone("beep-pause", 2).wav();
+        let samples = (0.1 * SAMPLE_RATE as f64) as usize * 4;
+
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(wav.len(), 44 + samples * 2);
+        assert_eq!(
+            u32::from_le_bytes(wav[40..44].try_into().unwrap()) as usize,
+            samples * 2
+        );
+
+        let sample = |index: usize| i16::from_le_bytes([wav[44 + index * 2], wav[45 + index * 2]]);
+        let length = samples / 4;
+        assert!((0..length).any(|index| sample(index) != 0));
+        assert!((length..length * 2).all(|index| sample(index) == 0));
+    }
+}
@@ -0,0 +1,74 @@
+pub fn split_arguments(input: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut started = false;
+
+    for char in input.chars() {
+        match (quote, char) {
+            (None, '\'' | '"') => {
+                quote = Some(char);
+                started = true;
+            }
+            (Some(q), _) if q == char => quote = None,
+            (None, _) if char.is_whitespace() => {
+                if started {
+                    arguments.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            _ => {
+                current.push(char);
+                started = true;
+            }
+        }
+    }
+
+    if started {
+        arguments.push(current);
+    }
+
+    arguments
+}
+
+pub fn quote_argument(argument: &str) -> String {
+    if !argument.is_empty()
+        && !argument
+            .chars()
+            .any(|char| char.is_whitespace() || char == '\'' || char == '"')
+    {
+        argument.to_string()
+    } else if argument.contains('"') {
+        format!("'{}'", argument)
+    } else {
+        format!("\"{}\"", argument)
+    }
+}
+
+pub fn join_arguments(arguments: &[String]) -> String {
+    arguments
+        .iter()
+        .map(|argument| quote_argument(argument))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_arguments_handles_quotes() {
+        assert_eq!(
+            split_arguments(r#"  -a "b c"  'd "e"' f""g '' "#),
+            ["-a", "b c", r#"d "e""#, "fg", ""]
+        );
+        assert!(split_arguments("   ").is_empty());
+    }
+
+    #[test]
+    fn join_arguments_round_trips() {
+        let arguments = ["plain", "with space", r#"say "hi""#, "it's", ""].map(String::from);
+        assert_eq!(split_arguments(&join_arguments(&arguments)), arguments);
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use crate::error::NormalError::{Input, NumberFormat};
+use crate::error::NormalResult;
+
+pub fn parse_duration(input: &str) -> NormalResult<Duration> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(Input);
+    }
+
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    for char in input.chars() {
+        if char.is_ascii_digit() {
+            number.push(char);
+            continue;
+        }
+
+        let unit = match char {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(Input),
+        };
+        let value = std::mem::take(&mut number)
+            .parse::<u64>()
+            .map_err(|_| NumberFormat)?;
+        seconds = value
+            .checked_mul(unit)
+            .and_then(|value| seconds.checked_add(value))
+            .ok_or(NumberFormat)?;
+    }
+
+    if !number.is_empty() {
+        let value = number.parse::<u64>().map_err(|_| NumberFormat)?;
+        seconds = seconds.checked_add(value).ok_or(NumberFormat)?;
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_accepts_units() {
+        let seconds = |input| parse_duration(input).unwrap().as_secs();
+        assert_eq!(seconds("90"), 90);
+        assert_eq!(seconds("1h30m"), 5400);
+        assert_eq!(seconds("2d"), 172800);
+        assert_eq!(seconds(" 1m30 "), 90);
+    }
+
+    #[test]
+    fn parse_duration_rejects_malformed_input() {
+        assert!(matches!(parse_duration(""), Err(Input)));
+        assert!(matches!(parse_duration("5x"), Err(Input)));
+        assert!(matches!(parse_duration("h"), Err(NumberFormat)));
+        assert!(matches!(
+            parse_duration("99999999999999999999d"),
+            Err(NumberFormat)
+        ));
+    }
+}
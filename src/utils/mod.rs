@@ -1 +1,3 @@
+pub mod arguments;
+pub mod duration;
 pub mod stdio;